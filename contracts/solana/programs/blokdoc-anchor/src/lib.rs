@@ -0,0 +1,8736 @@
+//! Blokdoc document verification and management program (Anchor)
+//!
+//! Registers document hashes under a global `DocumentManager`, and supports
+//! signing, versioning, delegation, ownership transfer and archiving.
+
+#![allow(clippy::result_large_err, clippy::too_many_arguments)]
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::{hash, hashv};
+use anchor_lang::solana_program::ed25519_program;
+use anchor_lang::solana_program::program::set_return_data;
+use anchor_lang::solana_program::sysvar::{self, instructions::load_instruction_at_checked};
+use anchor_lang::{system_program, Discriminator};
+use anchor_spl::token::{self, Token, TokenAccount};
+use spl_account_compression::{program::SplAccountCompression, Noop};
+pub use blokdoc_state::{decode_digest_32, is_lowercase_hex, VerificationResult, MAX_HASH_LEN};
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+/// Maximum length in bytes of a document name.
+pub const MAX_NAME_LEN: usize = 100;
+/// Maximum length in bytes of a document type.
+pub const MAX_DOC_TYPE_LEN: usize = 50;
+/// Maximum length in bytes of a signature hash.
+pub const MAX_SIGNATURE_HASH_LEN: usize = 64;
+/// Maximum length in bytes of a revocation reason.
+pub const MAX_REVOCATION_REASON_LEN: usize = 200;
+/// Maximum number of required signers on a document.
+pub const MAX_REQUIRED_SIGNERS: usize = 10;
+/// Number of previous owners kept on a document as provenance.
+pub const MAX_PREVIOUS_OWNERS: usize = 5;
+/// Maximum length in bytes of a MIME type.
+pub const MAX_MIME_TYPE_LEN: usize = 32;
+/// Maximum length in bytes of a structured metadata title.
+pub const MAX_TITLE_LEN: usize = 100;
+/// Maximum length in bytes of a structured metadata author.
+pub const MAX_AUTHOR_LEN: usize = 64;
+/// Maximum number of tags in structured metadata.
+pub const MAX_TAGS: usize = 8;
+/// Maximum length in bytes of a single tag.
+pub const MAX_TAG_LEN: usize = 32;
+/// Maximum number of `DocumentTag` accounts per document.
+pub const MAX_DOCUMENT_TAGS: u8 = 16;
+/// Maximum length in bytes of a category name; names are used as a PDA seed.
+pub const MAX_CATEGORY_NAME_LEN: usize = 32;
+/// Maximum length in bytes of an organization name; names are used as a PDA seed.
+pub const MAX_ORG_NAME_LEN: usize = 32;
+/// Maximum length in bytes of a collection name; names are used as a PDA seed.
+pub const MAX_COLLECTION_NAME_LEN: usize = 32;
+/// Maximum length in bytes of an attribute key; keys are used as a PDA seed.
+pub const MAX_ATTRIBUTE_KEY_LEN: usize = 32;
+/// Maximum length in bytes of an attribute value.
+pub const MAX_ATTRIBUTE_VALUE_LEN: usize = 256;
+/// Maximum length in bytes of a storage URI.
+pub const MAX_STORAGE_URI_LEN: usize = 80;
+/// Maximum length in bytes of an attestation statement hash.
+pub const MAX_STATEMENT_HASH_LEN: usize = 64;
+/// Maximum length in bytes of a notarization jurisdiction.
+pub const MAX_JURISDICTION_LEN: usize = 32;
+/// Maximum length in bytes of a dispute reason hash.
+pub const MAX_REASON_HASH_LEN: usize = 64;
+/// Maximum length in bytes of the role a signer signs in.
+pub const MAX_SIGNER_ROLE_LEN: usize = 32;
+/// Maximum length in bytes of a signature comment hash.
+pub const MAX_COMMENT_HASH_LEN: usize = 64;
+/// Maximum length in bytes of a wrapped content encryption key.
+pub const MAX_ENCRYPTED_KEY_LEN: usize = 256;
+/// Layout version written to new documents, signatures and the manager.
+/// Accounts created before versioning read back as 0.
+pub const CURRENT_SCHEMA_VERSION: u8 = 1;
+/// MIME types a document may be registered with.
+pub const ALLOWED_MIME_TYPES: [&str; 5] = [
+    "application/pdf",
+    "application/json",
+    "image/png",
+    "image/jpeg",
+    "text/plain",
+];
+
+/// Maximum number of documents in a single `register_documents_batch`. Each
+/// entry adds two accounts and two account-creation CPIs, so ten entries
+/// keep a legacy transaction within its 1232-byte size limit and well inside
+/// the default compute budget.
+pub const MAX_BATCH_SIZE: usize = 10;
+
+/// Maximum number of co-signers in a single `sign_document_multi`; each adds
+/// two accounts and an account-creation CPI.
+pub const MAX_CO_SIGNERS: usize = 5;
+
+/// Maximum number of documents in a single `archive_collection`; each adds
+/// one account and no CPI, so the limit is set by transaction size.
+pub const MAX_COLLECTION_ARCHIVE: usize = 20;
+
+/// Maximum number of siblings in a Merkle proof, bounding the hashing done
+/// by `verify_merkle_inclusion`. Batches hold at most `1 << 24` leaves.
+pub const MAX_MERKLE_PROOF_LEN: usize = 24;
+/// Maximum length in bytes of a Merkle batch description.
+pub const MAX_MERKLE_DESCRIPTION_LEN: usize = 128;
+
+/// Checks that `hash` is a digest produced by `algorithm`, encoded as
+/// lowercase hex characters.
+pub fn validate_hash(hash: &str, algorithm: HashAlgorithm) -> Result<()> {
+    require!(
+        hash.len() == algorithm.hex_len() && is_lowercase_hex(hash),
+        DocumentError::InvalidHashFormat
+    );
+    Ok(())
+}
+
+/// Checks that `mime_type` is one of `ALLOWED_MIME_TYPES`.
+pub fn validate_mime_type(mime_type: &str) -> Result<()> {
+    require!(
+        ALLOWED_MIME_TYPES.contains(&mime_type),
+        DocumentError::UnsupportedMimeType
+    );
+    Ok(())
+}
+
+/// Checks that `uri` is an `ipfs://<cid>` or `ar://<transaction id>`
+/// reference with an identifier of plausible length and alphabet.
+pub fn validate_storage_uri(uri: &str) -> Result<()> {
+    let valid = uri.len() <= MAX_STORAGE_URI_LEN
+        && if let Some(cid) = uri.strip_prefix("ipfs://") {
+            // CIDv0 is 46 base58 characters; CIDv1 strings are longer
+            (46..=64).contains(&cid.len()) && cid.bytes().all(|b| b.is_ascii_alphanumeric())
+        } else if let Some(id) = uri.strip_prefix("ar://") {
+            // Arweave ids are 32 bytes in unpadded base64url
+            id.len() == 43
+                && id
+                    .bytes()
+                    .all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_')
+        } else {
+            false
+        };
+    require!(valid, DocumentError::InvalidStorageUri);
+    Ok(())
+}
+
+/// Creates a program-owned account of `space` bytes at the PDA derived
+/// from `seeds`, which must be the address of `target`, and returns its
+/// bump.
+fn create_pda_account<'info>(
+    payer: &Signer<'info>,
+    target: &AccountInfo<'info>,
+    system_program: &Program<'info, System>,
+    space: usize,
+    seeds: &[&[u8]],
+) -> Result<u8> {
+    let (address, bump) = Pubkey::find_program_address(seeds, &ID);
+    require_keys_eq!(address, target.key(), ErrorCode::ConstraintSeeds);
+    
+    let bump_seed = [bump];
+    let mut signer_seeds = seeds.to_vec();
+    signer_seeds.push(&bump_seed);
+    let signer = &[&signer_seeds[..]];
+    let rent = Rent::get()?.minimum_balance(space);
+    
+    if target.lamports() == 0 {
+        system_program::create_account(
+            CpiContext::new_with_signer(
+                system_program.to_account_info(),
+                system_program::CreateAccount {
+                    from: payer.to_account_info(),
+                    to: target.clone(),
+                },
+                signer,
+            ),
+            rent,
+            space as u64,
+            &ID,
+        )?;
+        return Ok(bump);
+    }
+    
+    // Anyone can send lamports to the address first, which makes
+    // `create_account` fail; top it up, allocate and assign it instead, as
+    // Anchor's `init` does
+    let top_up = rent.saturating_sub(target.lamports());
+    if top_up > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                system_program.to_account_info(),
+                system_program::Transfer {
+                    from: payer.to_account_info(),
+                    to: target.clone(),
+                },
+            ),
+            top_up,
+        )?;
+    }
+    system_program::allocate(
+        CpiContext::new_with_signer(
+            system_program.to_account_info(),
+            system_program::Allocate {
+                account_to_allocate: target.clone(),
+            },
+            signer,
+        ),
+        space as u64,
+    )?;
+    system_program::assign(
+        CpiContext::new_with_signer(
+            system_program.to_account_info(),
+            system_program::Assign {
+                account_to_assign: target.clone(),
+            },
+            signer,
+        ),
+        &ID,
+    )?;
+    
+    Ok(bump)
+}
+
+/// Checks the signing rules a document is registered with: the required
+/// signers and threshold, or a signer sequence replacing both.
+fn validate_signing_rules(
+    required_signers: &[Pubkey],
+    signature_threshold: u8,
+    signer_sequence: &[Pubkey],
+) -> Result<()> {
+    require!(
+        required_signers.len() <= MAX_REQUIRED_SIGNERS,
+        DocumentError::TooManyRequiredSigners
+    );
+    require!(
+        required_signers.is_empty() || signature_threshold as usize <= required_signers.len(),
+        DocumentError::InvalidThreshold
+    );
+    Document::validate_signer_sequence(signer_sequence, required_signers, signature_threshold)
+}
+
+/// Leaf recording that `signer` signed `document` with `signature_hash` at
+/// `timestamp` in the document's compressed signature tree.
+pub fn compressed_signature_leaf(
+    document: &Pubkey,
+    signer: &Pubkey,
+    signature_hash: &str,
+    timestamp: i64,
+) -> [u8; 32] {
+    hashv(&[
+        document.as_ref(),
+        signer.as_ref(),
+        signature_hash.as_bytes(),
+        &timestamp.to_le_bytes(),
+    ])
+    .to_bytes()
+}
+
+/// Root of the Merkle tree holding `leaf` at position `index`, given the
+/// sibling hashes on its path from the bottom up, or `None` if `index` does
+/// not fit a tree of that depth. Leaves are hashed as `sha256(0x00 || leaf)`
+/// and inner nodes as `sha256(0x01 || left || right)`, so an inner node can
+/// never pass for a leaf. Bit `i` of `index` is set when the node at level
+/// `i` is a right child.
+pub fn merkle_root(leaf: &[u8; 32], index: u32, proof: &[[u8; 32]]) -> Option<[u8; 32]> {
+    if proof.len() < 32 && index >> proof.len() != 0 {
+        return None;
+    }
+    
+    let mut node = hashv(&[&[0x00], leaf]).to_bytes();
+    for (level, sibling) in proof.iter().enumerate() {
+        node = if index >> level & 1 == 0 {
+            hashv(&[&[0x01], &node, sibling]).to_bytes()
+        } else {
+            hashv(&[&[0x01], sibling, &node]).to_bytes()
+        };
+    }
+    Some(node)
+}
+
+/// Transfers a registration `fee` from `payer` into the treasury PDA. A
+/// zero fee skips the CPI entirely. The payer must stay rent-exempt, so a
+/// balance below the fee plus its rent minimum fails with `InsufficientFee`.
+fn collect_registration_fee<'info>(
+    payer: &Signer<'info>,
+    treasury: &SystemAccount<'info>,
+    system_program: &Program<'info, System>,
+    fee: u64,
+) -> Result<()> {
+    if fee == 0 {
+        return Ok(());
+    }
+    let rent_minimum = Rent::get()?.minimum_balance(payer.data_len());
+    require!(
+        payer.lamports() >= fee.saturating_add(rent_minimum),
+        DocumentError::InsufficientFee
+    );
+    
+    system_program::transfer(
+        CpiContext::new(
+            system_program.to_account_info(),
+            system_program::Transfer {
+                from: payer.to_account_info(),
+                to: treasury.to_account_info(),
+            },
+        ),
+        fee,
+    )
+}
+
+/// Address of the treasury PDA that collects registration fees.
+fn treasury_address() -> Pubkey {
+    Pubkey::find_program_address(&[b"treasury"], &ID).0
+}
+
+/// Lamports that can leave `account` while keeping it rent-exempt.
+fn withdrawable_lamports(account: &AccountInfo, rent: &Rent) -> u64 {
+    account
+        .lamports()
+        .saturating_sub(rent.minimum_balance(account.data_len()))
+}
+
+/// Transfers a registration fee of `amount` tokens of `fee_mint` from the
+/// payer's token account into a token account owned by the treasury PDA.
+fn collect_token_fee<'info>(
+    payer: &Signer<'info>,
+    payer_token_account: &Account<'info, TokenAccount>,
+    treasury_token_account: &Account<'info, TokenAccount>,
+    treasury: &SystemAccount<'info>,
+    token_program: &Program<'info, Token>,
+    fee_mint: Pubkey,
+    amount: u64,
+) -> Result<()> {
+    require!(
+        payer_token_account.mint == fee_mint && treasury_token_account.mint == fee_mint,
+        DocumentError::InvalidFeeMint
+    );
+    require_keys_eq!(
+        treasury_token_account.owner,
+        treasury.key(),
+        DocumentError::InvalidTreasuryTokenAccount
+    );
+    if amount == 0 {
+        return Ok(());
+    }
+    
+    token::transfer(
+        CpiContext::new(
+            token_program.to_account_info(),
+            token::Transfer {
+                from: payer_token_account.to_account_info(),
+                to: treasury_token_account.to_account_info(),
+                authority: payer.to_account_info(),
+            },
+        ),
+        amount,
+    )
+}
+
+/// Checks that `signer` may act for `principal` within `scope`, either
+/// because they are the same key or through an unexpired delegation.
+fn check_acting_for(
+    principal: &Pubkey,
+    signer: &Pubkey,
+    delegation: Option<&Account<Delegation>>,
+    scope: DelegationScope,
+) -> Result<()> {
+    if principal == signer {
+        return Ok(());
+    }
+    
+    let delegation = delegation.ok_or(DocumentError::Unauthorized)?;
+    require!(
+        delegation.authority == *principal && delegation.delegate == *signer,
+        DocumentError::Unauthorized
+    );
+    require!(
+        delegation.scope.permits(scope),
+        DocumentError::DelegationScopeMismatch
+    );
+    require!(
+        Clock::get()?.unix_timestamp < delegation.expires_at,
+        DocumentError::DelegationExpired
+    );
+    
+    Ok(())
+}
+
+/// Checks that `actor` holds `role` in `organization`. The organization
+/// admin, and members with the `Admin` role, hold every role.
+fn check_org_role(
+    organization: &Account<Organization>,
+    membership: Option<&Account<OrgMember>>,
+    actor: &Pubkey,
+    role: OrgRole,
+) -> Result<()> {
+    if organization.admin == *actor {
+        return Ok(());
+    }
+    
+    let membership = membership.ok_or(DocumentError::Unauthorized)?;
+    require!(
+        membership.organization == organization.key() && membership.member == *actor,
+        DocumentError::Unauthorized
+    );
+    require!(
+        membership.role == role || membership.role == OrgRole::Admin,
+        DocumentError::InsufficientOrgRole
+    );
+    
+    Ok(())
+}
+
+/// Records a signature on a document and advances the document's status.
+fn record_signature(
+    accounts: &mut SignDocument,
+    signature_hash: String,
+    kind: SignatureKind,
+    role: Option<String>,
+    comment_hash: Option<String>,
+    valid_until: Option<i64>,
+    verified: bool,
+    bump: u8,
+) -> Result<()> {
+    let document = &mut accounts.document;
+    let signature = &mut accounts.signature;
+    
+    let clock = Clock::get()?;
+    let now = clock.unix_timestamp;
+    
+    require!(!accounts.document_manager.paused, DocumentError::ProgramPaused);
+    document.require_mutable()?;
+    
+    // Signatures are recorded for the principal, who may sign through a delegate
+    let principal = accounts.principal.key();
+    check_acting_for(
+        &principal,
+        &accounts.signer.key(),
+        accounts.delegation.as_ref(),
+        DelegationScope::Sign,
+    )?;
+    
+    require!(
+        signature_hash.len() <= MAX_SIGNATURE_HASH_LEN,
+        DocumentError::SignatureHashTooLong
+    );
+    require!(
+        role.as_ref().map_or(0, String::len) <= MAX_SIGNER_ROLE_LEN,
+        DocumentError::SignerRoleTooLong
+    );
+    require!(
+        comment_hash.as_ref().map_or(0, String::len) <= MAX_COMMENT_HASH_LEN,
+        DocumentError::CommentHashTooLong
+    );
+    if let Some(valid_until) = valid_until {
+        require!(valid_until > now, DocumentError::InvalidDeadline);
+    }
+    document.require_active()?;
+    require!(!document.is_expired(now), DocumentError::DocumentExpired);
+    require!(
+        !document.signing_period_ended(now),
+        DocumentError::SigningPeriodEnded
+    );
+    // Witnesses and acknowledgements do not decide the outcome, so anyone may record them
+    require!(
+        !kind.is_decision()
+            || document.required_signers.is_empty()
+            || document.required_signers.contains(&principal),
+        DocumentError::SignerNotAuthorized
+    );
+    
+    // The signature PDA is unique per signer; revoking closes it so the signer can sign again
+    require!(
+        signature.signer == Pubkey::default(),
+        DocumentError::AlreadySigned
+    );
+    if kind.is_decision() {
+        document.check_sequence(&principal)?;
+    }
+    
+    signature.document = document.key();
+    signature.signer = principal;
+    signature.signature_hash = signature_hash;
+    signature.timestamp = now;
+    signature.kind = kind;
+    signature.document_version = document.version;
+    signature.slot = clock.slot;
+    signature.role = role;
+    signature.comment_hash = comment_hash;
+    signature.valid_until = valid_until;
+    signature.bump = bump;
+    signature.schema_version = CURRENT_SCHEMA_VERSION;
+    
+    if let Some(signature_request) = &mut accounts.signature_request {
+        if signature_request.status == SignatureRequestStatus::Pending {
+            signature_request.status = SignatureRequestStatus::Fulfilled;
+        }
+    }
+    
+    emit!(DocumentSigned {
+        document_id: document.key(),
+        signer: signature.signer,
+        signature_id: signature.key(),
+        kind,
+        document_version: signature.document_version,
+        verified,
+        timestamp: signature.timestamp,
+        slot: signature.slot,
+        role: signature.role.clone(),
+        comment_hash: signature.comment_hash.clone(),
+    });
+    
+    let document_manager = &mut accounts.document_manager;
+    document_manager.total_signatures = document_manager
+        .total_signatures
+        .checked_add(1)
+        .ok_or(DocumentError::CounterOverflow)?;
+    document_manager.last_activity_ts = now;
+    
+    match kind {
+        SignatureKind::Approve => document.record_approval(&principal, signature.valid_until)?,
+        SignatureKind::Reject => {
+            document.status = DocumentStatus::Rejected;
+            return Ok(());
+        }
+        SignatureKind::Witness | SignatureKind::Acknowledge => return Ok(()),
+    }
+    
+    mark_if_fully_executed(document, now)
+}
+
+/// Moves the document to `FullyExecuted` once its approvals reach the
+/// completion threshold.
+fn mark_if_fully_executed(document: &mut Account<Document>, now: i64) -> Result<()> {
+    if document.threshold_reached(now) {
+        document.status = DocumentStatus::FullyExecuted;
+        
+        emit!(DocumentFullyExecuted {
+            document_id: document.key(),
+            signatures_count: document.signatures_count,
+            timestamp: now,
+        });
+    }
+    
+    Ok(())
+}
+
+/// Checks that the transaction carries an ed25519 program instruction
+/// verifying a signature by `signer` over `message`. The runtime rejects the
+/// whole transaction if that signature is invalid, so finding the
+/// instruction is enough.
+fn verify_ed25519_instruction(
+    instructions: &AccountInfo,
+    signer: &Pubkey,
+    message: &[u8],
+) -> Result<()> {
+    let mut index = 0;
+    while let Ok(ix) = load_instruction_at_checked(index, instructions) {
+        if ix.program_id == ed25519_program::ID
+            && ed25519_data_matches(&ix.data, signer, message)
+        {
+            return Ok(());
+        }
+        index += 1;
+    }
+    
+    err!(DocumentError::InvalidSignature)
+}
+
+/// Whether ed25519 instruction `data` verifies a single signature by
+/// `signer` over `message`, with all of its data embedded in the
+/// instruction itself.
+fn ed25519_data_matches(data: &[u8], signer: &Pubkey, message: &[u8]) -> bool {
+    // Header: signature count and padding, then one 14-byte offsets block
+    const HEADER_LEN: usize = 2;
+    const OFFSETS_LEN: usize = 14;
+    const PUBKEY_LEN: usize = 32;
+    const SIGNATURE_LEN: usize = 64;
+    
+    if data.len() < HEADER_LEN + OFFSETS_LEN || data[0] != 1 {
+        return false;
+    }
+    let read_u16 = |at: usize| u16::from_le_bytes([data[at], data[at + 1]]);
+    let offsets = HEADER_LEN;
+    let signature_offset = read_u16(offsets) as usize;
+    let signature_ix = read_u16(offsets + 2);
+    let pubkey_offset = read_u16(offsets + 4) as usize;
+    let pubkey_ix = read_u16(offsets + 6);
+    let message_offset = read_u16(offsets + 8) as usize;
+    let message_len = read_u16(offsets + 10) as usize;
+    let message_ix = read_u16(offsets + 12);
+    
+    // u16::MAX means the data lives in the ed25519 instruction itself
+    signature_ix == u16::MAX
+        && pubkey_ix == u16::MAX
+        && message_ix == u16::MAX
+        && signature_offset + SIGNATURE_LEN <= data.len()
+        && data.get(pubkey_offset..pubkey_offset + PUBKEY_LEN) == Some(signer.as_ref())
+        && data.get(message_offset..message_offset + message_len) == Some(message)
+}
+
+#[program]
+pub mod blokdoc {
+    use super::*;
+
+    pub fn initialize(ctx: Context<Initialize>) -> Result<()> {
+        let document_manager = &mut ctx.accounts.document_manager;
+        document_manager.authority = ctx.accounts.authority.key();
+        document_manager.document_count = 0;
+        document_manager.pending_authority = None;
+        document_manager.paused = false;
+        document_manager.registration_mode = RegistrationMode::Open;
+        document_manager.registration_fee_lamports = 0;
+        document_manager.fee_mint = None;
+        document_manager.token_fee_amount = 0;
+        document_manager.max_documents_per_authority = 0;
+        document_manager.archived_count = 0;
+        document_manager.total_signatures = 0;
+        document_manager.last_activity_ts = 0;
+        document_manager.bump = ctx.bumps["document_manager"];
+        document_manager.schema_version = CURRENT_SCHEMA_VERSION;
+        document_manager.treasury = treasury_address();
+        Ok(())
+    }
+    
+    /// Halts new registrations and signatures. Archiving and closing stay
+    /// available so users can wind down while paused.
+    pub fn pause(ctx: Context<SetPaused>) -> Result<()> {
+        let document_manager = &mut ctx.accounts.document_manager;
+        
+        require!(
+            document_manager.authority == ctx.accounts.authority.key(),
+            DocumentError::Unauthorized
+        );
+        
+        document_manager.paused = true;
+        
+        emit!(ProgramPaused {
+            authority: ctx.accounts.authority.key(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+    
+    /// Resumes registrations and signatures after a `pause`.
+    pub fn unpause(ctx: Context<SetPaused>) -> Result<()> {
+        let document_manager = &mut ctx.accounts.document_manager;
+        
+        require!(
+            document_manager.authority == ctx.accounts.authority.key(),
+            DocumentError::Unauthorized
+        );
+        
+        document_manager.paused = false;
+        
+        emit!(ProgramUnpaused {
+            authority: ctx.accounts.authority.key(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+    
+    pub fn set_registration_mode(
+        ctx: Context<SetRegistrationMode>,
+        registration_mode: RegistrationMode,
+    ) -> Result<()> {
+        let document_manager = &mut ctx.accounts.document_manager;
+        
+        require!(
+            document_manager.authority == ctx.accounts.authority.key(),
+            DocumentError::Unauthorized
+        );
+        
+        document_manager.registration_mode = registration_mode;
+        
+        Ok(())
+    }
+    
+    /// Registers a category documents can be filed under.
+    pub fn create_category(ctx: Context<CreateCategory>, name: String) -> Result<()> {
+        require!(
+            ctx.accounts.document_manager.authority == ctx.accounts.authority.key(),
+            DocumentError::Unauthorized
+        );
+        require!(
+            name.len() <= MAX_CATEGORY_NAME_LEN,
+            DocumentError::CategoryNameTooLong
+        );
+        
+        let category = &mut ctx.accounts.category;
+        let now = Clock::get()?.unix_timestamp;
+        category.name = name;
+        category.created_at = now;
+        
+        emit!(CategoryCreated {
+            category: category.key(),
+            name: category.name.clone(),
+            authority: ctx.accounts.authority.key(),
+            timestamp: now,
+        });
+        
+        Ok(())
+    }
+    
+    /// Creates a collection owned by the caller, such as a legal case, to
+    /// file documents under.
+    pub fn create_collection(ctx: Context<CreateCollection>, name: String) -> Result<()> {
+        require!(
+            name.len() <= MAX_COLLECTION_NAME_LEN,
+            DocumentError::CollectionNameTooLong
+        );
+        
+        let collection = &mut ctx.accounts.collection;
+        let now = Clock::get()?.unix_timestamp;
+        collection.authority = ctx.accounts.authority.key();
+        collection.name = name;
+        collection.documents_in_collection = 0;
+        collection.created_at = now;
+        
+        emit!(CollectionCreated {
+            collection: collection.key(),
+            authority: collection.authority,
+            name: collection.name.clone(),
+            timestamp: now,
+        });
+        
+        Ok(())
+    }
+    
+    /// Archives every document passed in `remaining_accounts`, up to
+    /// `MAX_COLLECTION_ARCHIVE`. Each must be filed in the collection and
+    /// owned by its authority.
+    pub fn archive_collection<'info>(
+        ctx: Context<'_, '_, '_, 'info, ArchiveCollection<'info>>,
+    ) -> Result<()> {
+        let collection = &ctx.accounts.collection;
+        let authority = ctx.accounts.authority.key();
+        let now = Clock::get()?.unix_timestamp;
+        
+        require!(
+            collection.authority == authority,
+            DocumentError::NotCollectionAuthority
+        );
+        require!(
+            ctx.remaining_accounts.len() <= MAX_COLLECTION_ARCHIVE,
+            DocumentError::BatchTooLarge
+        );
+        
+        for document_info in ctx.remaining_accounts {
+            let mut document = Account::<Document>::try_from(document_info)?;
+            
+            require!(
+                document.collection == Some(collection.key()),
+                DocumentError::DocumentNotInCollection
+            );
+            document.require_mutable()?;
+            require!(document.authority == authority, DocumentError::Unauthorized);
+            require!(
+                document.status != DocumentStatus::Revoked,
+                DocumentError::DocumentRevoked
+            );
+            require!(
+                document.status != DocumentStatus::Archived,
+                DocumentError::AlreadyArchived
+            );
+            
+            document.status = DocumentStatus::Archived;
+            
+            emit!(DocumentArchived {
+                document_id: document.key(),
+                authority: document.authority,
+                timestamp: now,
+            });
+            
+            document.exit(&ID)?;
+        }
+        
+        let document_manager = &mut ctx.accounts.document_manager;
+        document_manager.archived_count = document_manager
+            .archived_count
+            .checked_add(ctx.remaining_accounts.len() as u64)
+            .ok_or(DocumentError::CounterOverflow)?;
+        document_manager.last_activity_ts = now;
+        
+        Ok(())
+    }
+    
+    /// Refiles a document from one collection into another; both
+    /// collections' authorities must sign.
+    pub fn move_document(ctx: Context<MoveDocument>) -> Result<()> {
+        ctx.accounts.document.require_mutable()?;
+        
+        let document = &mut ctx.accounts.document;
+        let from_collection = &mut ctx.accounts.from_collection;
+        let to_collection = &mut ctx.accounts.to_collection;
+        
+        require_keys_neq!(
+            from_collection.key(),
+            to_collection.key(),
+            DocumentError::SameCollection
+        );
+        require!(
+            document.collection == Some(from_collection.key()),
+            DocumentError::DocumentNotInCollection
+        );
+        require!(
+            from_collection.authority == ctx.accounts.from_authority.key()
+                && to_collection.authority == ctx.accounts.to_authority.key(),
+            DocumentError::NotCollectionAuthority
+        );
+        
+        from_collection.documents_in_collection =
+            from_collection.documents_in_collection.saturating_sub(1);
+        to_collection.documents_in_collection = to_collection
+            .documents_in_collection
+            .checked_add(1)
+            .ok_or(DocumentError::CounterOverflow)?;
+        document.collection = Some(to_collection.key());
+        
+        emit!(DocumentMoved {
+            document_id: document.key(),
+            from_collection: from_collection.key(),
+            to_collection: to_collection.key(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+    
+    /// Creates an organization administered by the caller. Documents
+    /// registered through it are owned by the organization account.
+    pub fn create_organization(ctx: Context<CreateOrganization>, name: String) -> Result<()> {
+        require!(name.len() <= MAX_ORG_NAME_LEN, DocumentError::OrgNameTooLong);
+        
+        let organization = &mut ctx.accounts.organization;
+        let now = Clock::get()?.unix_timestamp;
+        organization.admin = ctx.accounts.admin.key();
+        organization.name = name;
+        organization.member_count = 0;
+        organization.created_at = now;
+        
+        emit!(OrganizationCreated {
+            organization: organization.key(),
+            admin: organization.admin,
+            name: organization.name.clone(),
+            timestamp: now,
+        });
+        
+        Ok(())
+    }
+    
+    pub fn add_member(ctx: Context<AddMember>, member: Pubkey, role: OrgRole) -> Result<()> {
+        let organization = &mut ctx.accounts.organization;
+        
+        require!(
+            organization.admin == ctx.accounts.admin.key(),
+            DocumentError::Unauthorized
+        );
+        
+        let org_member = &mut ctx.accounts.org_member;
+        let now = Clock::get()?.unix_timestamp;
+        org_member.organization = organization.key();
+        org_member.member = member;
+        org_member.role = role;
+        org_member.added_at = now;
+        organization.member_count = organization
+            .member_count
+            .checked_add(1)
+            .ok_or(DocumentError::CounterOverflow)?;
+        
+        emit!(MemberAdded {
+            organization: organization.key(),
+            member,
+            role,
+            timestamp: now,
+        });
+        
+        Ok(())
+    }
+    
+    pub fn remove_member(ctx: Context<RemoveMember>) -> Result<()> {
+        let organization = &mut ctx.accounts.organization;
+        
+        require!(
+            organization.admin == ctx.accounts.admin.key(),
+            DocumentError::Unauthorized
+        );
+        
+        organization.member_count = organization
+            .member_count
+            .checked_sub(1)
+            .ok_or(DocumentError::CounterUnderflow)?;
+        
+        emit!(MemberRemoved {
+            organization: organization.key(),
+            member: ctx.accounts.org_member.member,
+            role: ctx.accounts.org_member.role,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+    
+    /// Sets the fee charged per registered document, in lamports.
+    pub fn set_registration_fee(
+        ctx: Context<SetRegistrationFee>,
+        registration_fee_lamports: u64,
+    ) -> Result<()> {
+        let document_manager = &mut ctx.accounts.document_manager;
+        
+        require!(
+            document_manager.authority == ctx.accounts.authority.key(),
+            DocumentError::Unauthorized
+        );
+        
+        document_manager.registration_fee_lamports = registration_fee_lamports;
+        
+        Ok(())
+    }
+    
+    /// Former name of `set_registration_fee`, kept for existing clients.
+    pub fn set_fee(ctx: Context<SetRegistrationFee>, registration_fee_lamports: u64) -> Result<()> {
+        set_registration_fee(ctx, registration_fee_lamports)
+    }
+    
+    /// Caps how many open documents each authority may have registered;
+    /// 0 means unlimited.
+    pub fn set_authority_quota(
+        ctx: Context<SetAuthorityQuota>,
+        max_documents_per_authority: u64,
+    ) -> Result<()> {
+        let document_manager = &mut ctx.accounts.document_manager;
+        
+        require!(
+            document_manager.authority == ctx.accounts.authority.key(),
+            DocumentError::Unauthorized
+        );
+        
+        document_manager.max_documents_per_authority = max_documents_per_authority;
+        
+        Ok(())
+    }
+    
+    /// Lets registrations pay `token_fee_amount` of `fee_mint` instead of the
+    /// lamport fee. Passing `None` disables token payments.
+    pub fn set_token_fee(
+        ctx: Context<SetTokenFee>,
+        fee_mint: Option<Pubkey>,
+        token_fee_amount: u64,
+    ) -> Result<()> {
+        let document_manager = &mut ctx.accounts.document_manager;
+        
+        require!(
+            document_manager.authority == ctx.accounts.authority.key(),
+            DocumentError::Unauthorized
+        );
+        
+        document_manager.fee_mint = fee_mint;
+        document_manager.token_fee_amount = token_fee_amount;
+        
+        Ok(())
+    }
+    
+    /// Moves `amount` lamports of collected fees from the treasury to the
+    /// manager authority. The treasury keeps its rent-exempt minimum.
+    pub fn withdraw_treasury(ctx: Context<WithdrawTreasury>, amount: u64) -> Result<()> {
+        require!(
+            ctx.accounts.document_manager.authority == ctx.accounts.authority.key(),
+            DocumentError::Unauthorized
+        );
+        let treasury = ctx.accounts.treasury.to_account_info();
+        require!(
+            amount <= withdrawable_lamports(&treasury, &Rent::get()?),
+            DocumentError::InsufficientTreasuryBalance
+        );
+        
+        system_program::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.treasury.to_account_info(),
+                    to: ctx.accounts.authority.to_account_info(),
+                },
+                &[&[b"treasury", &[ctx.bumps["treasury"]]]],
+            ),
+            amount,
+        )
+    }
+    
+    /// Allows `registrar` to register documents while the manager is in
+    /// `RegistrationMode::Allowlisted`.
+    pub fn add_registrar(ctx: Context<AddRegistrar>, registrar: Pubkey) -> Result<()> {
+        require!(
+            ctx.accounts.document_manager.authority == ctx.accounts.authority.key(),
+            DocumentError::Unauthorized
+        );
+        
+        let allowlist_entry = &mut ctx.accounts.allowlist_entry;
+        let now = Clock::get()?.unix_timestamp;
+        allowlist_entry.registrar = registrar;
+        allowlist_entry.added_at = now;
+        
+        emit!(RegistrarAdded {
+            registrar,
+            authority: ctx.accounts.authority.key(),
+            timestamp: now,
+        });
+        
+        Ok(())
+    }
+    
+    pub fn remove_registrar(ctx: Context<RemoveRegistrar>) -> Result<()> {
+        require!(
+            ctx.accounts.document_manager.authority == ctx.accounts.authority.key(),
+            DocumentError::Unauthorized
+        );
+        
+        emit!(RegistrarRemoved {
+            registrar: ctx.accounts.allowlist_entry.registrar,
+            authority: ctx.accounts.authority.key(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+    
+    /// Registers `notary`, or reactivates a previously removed one.
+    pub fn add_notary(ctx: Context<AddNotary>, notary: Pubkey) -> Result<()> {
+        require!(
+            ctx.accounts.document_manager.authority == ctx.accounts.authority.key(),
+            DocumentError::Unauthorized
+        );
+        
+        let notary_entry = &mut ctx.accounts.notary_entry;
+        require!(!notary_entry.active, DocumentError::NotaryAlreadyActive);
+        
+        let now = Clock::get()?.unix_timestamp;
+        notary_entry.notary = notary;
+        notary_entry.active = true;
+        notary_entry.added_at = now;
+        let notary_registry = &mut ctx.accounts.notary_registry;
+        notary_registry.active_notaries = notary_registry
+            .active_notaries
+            .checked_add(1)
+            .ok_or(DocumentError::CounterOverflow)?;
+        
+        emit!(NotaryAdded {
+            notary,
+            authority: ctx.accounts.authority.key(),
+            timestamp: now,
+        });
+        
+        Ok(())
+    }
+    
+    /// Stops `notary` from notarizing. The entry is kept so existing
+    /// notarizations still point at a known notary.
+    pub fn remove_notary(ctx: Context<RemoveNotary>) -> Result<()> {
+        require!(
+            ctx.accounts.document_manager.authority == ctx.accounts.authority.key(),
+            DocumentError::Unauthorized
+        );
+        
+        let notary_entry = &mut ctx.accounts.notary_entry;
+        require!(notary_entry.active, DocumentError::NotaryNotActive);
+        
+        notary_entry.active = false;
+        let notary_registry = &mut ctx.accounts.notary_registry;
+        notary_registry.active_notaries = notary_registry
+            .active_notaries
+            .checked_sub(1)
+            .ok_or(DocumentError::CounterUnderflow)?;
+        
+        emit!(NotaryRemoved {
+            notary: notary_entry.notary,
+            authority: ctx.accounts.authority.key(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+    
+    /// Proposes `new_authority` as manager authority. It takes effect once
+    /// the new authority calls `accept_manager_authority`.
+    pub fn transfer_manager_authority(
+        ctx: Context<TransferManagerAuthority>,
+        new_authority: Pubkey,
+    ) -> Result<()> {
+        let document_manager = &mut ctx.accounts.document_manager;
+        
+        require!(
+            document_manager.authority == ctx.accounts.authority.key(),
+            DocumentError::Unauthorized
+        );
+        require!(
+            new_authority != document_manager.authority,
+            DocumentError::SelfTransfer
+        );
+        
+        document_manager.pending_authority = Some(new_authority);
+        
+        emit!(ManagerAuthorityProposed {
+            authority: document_manager.authority,
+            pending_authority: new_authority,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+    
+    pub fn accept_manager_authority(ctx: Context<AcceptManagerAuthority>) -> Result<()> {
+        let document_manager = &mut ctx.accounts.document_manager;
+        let new_authority = ctx.accounts.new_authority.key();
+        
+        let pending_authority = document_manager
+            .pending_authority
+            .ok_or(DocumentError::NoPendingTransfer)?;
+        require!(pending_authority == new_authority, DocumentError::Unauthorized);
+        
+        let old_authority = document_manager.authority;
+        document_manager.authority = new_authority;
+        document_manager.pending_authority = None;
+        
+        emit!(ManagerAuthorityTransferred {
+            old_authority,
+            new_authority,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+
+    pub fn register_document(
+        ctx: Context<RegisterDocument>,
+        document_hash: String,
+        document_name: String,
+        document_type: String,
+        expires_at: Option<i64>,
+        hash_algorithm: HashAlgorithm,
+        required_signers: Vec<Pubkey>,
+        signature_threshold: u8,
+        content_length: u64,
+        mime_type: String,
+        storage_uri: Option<String>,
+        requires_review: bool,
+        reviewer: Option<Pubkey>,
+        signer_sequence: Vec<Pubkey>,
+        signing_deadline: Option<i64>,
+    ) -> Result<()> {
+        require!(!ctx.accounts.document_manager.paused, DocumentError::ProgramPaused);
+        ctx.accounts.document_manager.check_registrar(
+            &ctx.accounts.authority.key(),
+            ctx.accounts.allowlist_entry.as_ref(),
+        )?;
+        require!(document_hash.len() <= MAX_HASH_LEN, DocumentError::HashTooLong);
+        require!(document_name.len() <= MAX_NAME_LEN, DocumentError::NameTooLong);
+        require!(document_type.len() <= MAX_DOC_TYPE_LEN, DocumentError::TypeTooLong);
+        validate_hash(&document_hash, hash_algorithm)?;
+        validate_signing_rules(&required_signers, signature_threshold, &signer_sequence)?;
+        validate_mime_type(&mime_type)?;
+        if let Some(storage_uri) = &storage_uri {
+            validate_storage_uri(storage_uri)?;
+        }
+        require!(!requires_review || reviewer.is_some(), DocumentError::ReviewerRequired);
+        let category = match &ctx.accounts.category {
+            Some(category) => Some(Category::check(category)?),
+            None => None,
+        };
+        let parent = match &ctx.accounts.parent {
+            Some(parent) => Some(Document::check_parent(parent)?),
+            None => None,
+        };
+        let owner = match &ctx.accounts.organization {
+            Some(organization) => {
+                check_org_role(
+                    organization,
+                    ctx.accounts.org_member.as_ref(),
+                    &ctx.accounts.authority.key(),
+                    OrgRole::Registrar,
+                )?;
+                organization.key()
+            }
+            None => ctx.accounts.authority.key(),
+        };
+        
+        // Pay in tokens when the manager accepts them and the token accounts
+        // are passed, in lamports otherwise
+        let (fee, fee_mint) = match (
+            ctx.accounts.document_manager.fee_mint,
+            &ctx.accounts.payer_token_account,
+            &ctx.accounts.treasury_token_account,
+            &ctx.accounts.token_program,
+        ) {
+            (
+                Some(fee_mint),
+                Some(payer_token_account),
+                Some(treasury_token_account),
+                Some(token_program),
+            ) => {
+                let amount = ctx.accounts.document_manager.token_fee_amount;
+                collect_token_fee(
+                    &ctx.accounts.authority,
+                    payer_token_account,
+                    treasury_token_account,
+                    &ctx.accounts.treasury,
+                    token_program,
+                    fee_mint,
+                    amount,
+                )?;
+                (amount, Some(fee_mint))
+            }
+            _ => {
+                let amount = ctx.accounts.document_manager.registration_fee_lamports;
+                collect_registration_fee(
+                    &ctx.accounts.authority,
+                    &ctx.accounts.treasury,
+                    &ctx.accounts.system_program,
+                    amount,
+                )?;
+                (amount, None)
+            }
+        };
+        
+        let document_manager = &mut ctx.accounts.document_manager;
+        let authority_stats = &mut ctx.accounts.authority_stats;
+        let document = &mut ctx.accounts.document;
+        let hash_index = &mut ctx.accounts.hash_index;
+        let clock = Clock::get()?;
+        let now = clock.unix_timestamp;
+        
+        if let Some(signing_deadline) = signing_deadline {
+            require!(signing_deadline > now, DocumentError::InvalidDeadline);
+        }
+        if hash_index.document != Pubkey::default() {
+            return Err(hash_index.duplicate_error(&owner).into());
+        }
+        document_manager.check_quota(authority_stats.documents_owned, 1)?;
+        
+        document.authority = owner;
+        document.document_hash = document_hash;
+        document.hash_algorithm = hash_algorithm;
+        document.document_name = document_name;
+        document.document_type = document_type;
+        document.created_at = now;
+        document.updated_at = now;
+        document.slot = clock.slot;
+        document.status = if requires_review {
+            DocumentStatus::PendingReview
+        } else {
+            DocumentStatus::Active
+        };
+        document.version = 1;
+        document.signatures_count = 0;
+        document.expires_at = expires_at;
+        document.pending_owner = None;
+        document.revocation_reason = None;
+        document.required_signers = required_signers;
+        document.signature_threshold = signature_threshold;
+        document.content_length = content_length;
+        document.mime_type = mime_type;
+        document.delegate = None;
+        document.metadata = None;
+        document.storage_uri = storage_uri;
+        document.category = category;
+        document.parent = parent;
+        document.index = authority_stats.document_count;
+        document.registrant = ctx.accounts.authority.key();
+        document.document_hash_bytes = decode_digest_32(&document.document_hash);
+        document.reviewer = if requires_review { reviewer } else { None };
+        document.rejection_reason = None;
+        document.signer_sequence = signer_sequence;
+        document.signing_deadline = signing_deadline;
+        document.bump = ctx.bumps["document"];
+        document.schema_version = CURRENT_SCHEMA_VERSION;
+        
+        if let Some(collection) = &mut ctx.accounts.collection {
+            require!(
+                collection.authority == ctx.accounts.authority.key(),
+                DocumentError::NotCollectionAuthority
+            );
+            collection.documents_in_collection = collection
+                .documents_in_collection
+                .checked_add(1)
+                .ok_or(DocumentError::CounterOverflow)?;
+            document.collection = Some(collection.key());
+        }
+        
+        hash_index.document = document.key();
+        hash_index.authority = document.authority;
+        hash_index.timestamp = now;
+        
+        authority_stats.authority = ctx.accounts.authority.key();
+        authority_stats.document_count = authority_stats
+            .document_count
+            .checked_add(1)
+            .ok_or(DocumentError::CounterOverflow)?;
+        authority_stats.documents_owned = authority_stats
+            .documents_owned
+            .checked_add(1)
+            .ok_or(DocumentError::CounterOverflow)?;
+        document_manager.document_count = document_manager
+            .document_count
+            .checked_add(1)
+            .ok_or(DocumentError::CounterOverflow)?;
+        document_manager.last_activity_ts = now;
+        
+        emit!(DocumentRegistered {
+            document_id: document.key(),
+            authority: document.authority,
+            document_hash: document.document_hash.clone(),
+            hash_algorithm: document.hash_algorithm,
+            timestamp: document.created_at,
+            slot: document.slot,
+            expires_at: document.expires_at,
+            fee,
+            fee_mint,
+            storage_uri: document.storage_uri.clone(),
+            parent: document.parent,
+        });
+        
+        Ok(())
+    }
+    
+    /// Registers up to `MAX_BATCH_SIZE` documents. For each entry,
+    /// `remaining_accounts` must hold the document PDA followed by its hash
+    /// index PDA, in the same order as `documents`. Entries naming a
+    /// category or parent must name the `category` or `parent` account
+    /// passed with the batch. Any invalid entry fails the whole batch.
+    pub fn register_documents_batch<'info>(
+        ctx: Context<'_, '_, '_, 'info, RegisterDocumentsBatch<'info>>,
+        documents: Vec<DocumentInput>,
+    ) -> Result<()> {
+        require!(!ctx.accounts.document_manager.paused, DocumentError::ProgramPaused);
+        ctx.accounts.document_manager.check_registrar(
+            &ctx.accounts.authority.key(),
+            ctx.accounts.allowlist_entry.as_ref(),
+        )?;
+        require!(documents.len() <= MAX_BATCH_SIZE, DocumentError::BatchTooLarge);
+        require!(
+            ctx.remaining_accounts.len() == documents.len() * 2,
+            ErrorCode::AccountNotEnoughKeys
+        );
+        
+        let authority = &ctx.accounts.authority;
+        let system_program = &ctx.accounts.system_program;
+        let authority_stats = &mut ctx.accounts.authority_stats;
+        let clock = Clock::get()?;
+        let now = clock.unix_timestamp;
+        let batch_size = documents.len() as u64;
+        let fee = ctx.accounts.document_manager.registration_fee_lamports;
+        let category = match &ctx.accounts.category {
+            Some(category) => Some(Category::check(category)?),
+            None => None,
+        };
+        let parent = match &ctx.accounts.parent {
+            Some(parent) => Some(Document::check_parent(parent)?),
+            None => None,
+        };
+        
+        collect_registration_fee(
+            authority,
+            &ctx.accounts.treasury,
+            system_program,
+            fee.saturating_mul(batch_size),
+        )?;
+        
+        ctx.accounts
+            .document_manager
+            .check_quota(authority_stats.documents_owned, batch_size)?;
+        authority_stats.authority = authority.key();
+        authority_stats.documents_owned = authority_stats
+            .documents_owned
+            .checked_add(batch_size)
+            .ok_or(DocumentError::CounterOverflow)?;
+        
+        for (input, accounts) in documents.into_iter().zip(ctx.remaining_accounts.chunks(2)) {
+            input.validate()?;
+            if let Some(signing_deadline) = input.signing_deadline {
+                require!(signing_deadline > now, DocumentError::InvalidDeadline);
+            }
+            if input.category.is_some() {
+                require!(input.category == category, DocumentError::UnknownCategory);
+            }
+            if input.parent.is_some() {
+                require!(input.parent == parent, DocumentError::InvalidParent);
+            }
+            let (document_info, hash_index_info) = (&accounts[0], &accounts[1]);
+            
+            let index = authority_stats.document_count.to_le_bytes();
+            let document_bump = create_pda_account(
+                authority,
+                document_info,
+                system_program,
+                Document::LEN,
+                &[b"document", authority.key.as_ref(), &index],
+            )?;
+            
+            // Only an account this program owns is a registration; lamports
+            // alone may have been sent by anyone
+            if hash_index_info.owner == &ID {
+                let existing = Account::<HashIndex>::try_from(hash_index_info)?;
+                return Err(existing.duplicate_error(authority.key).into());
+            }
+            create_pda_account(
+                authority,
+                hash_index_info,
+                system_program,
+                HashIndex::LEN,
+                &[b"hash", hash(input.document_hash.as_bytes()).as_ref()],
+            )?;
+            
+            let mut document = Account::<Document>::try_from_unchecked(document_info)?;
+            document.authority = authority.key();
+            document.document_hash = input.document_hash;
+            document.hash_algorithm = input.hash_algorithm;
+            document.document_name = input.document_name;
+            document.document_type = input.document_type;
+            document.created_at = now;
+            document.updated_at = now;
+            document.slot = clock.slot;
+            document.status = DocumentStatus::Active;
+            document.version = 1;
+            document.content_length = input.content_length;
+            document.mime_type = input.mime_type;
+            document.index = authority_stats.document_count;
+            document.registrant = authority.key();
+            document.document_hash_bytes = decode_digest_32(&document.document_hash);
+            document.metadata = input.metadata;
+            document.expires_at = input.expires_at;
+            document.required_signers = input.required_signers;
+            document.signature_threshold = input.signature_threshold;
+            document.signer_sequence = input.signer_sequence;
+            document.signing_deadline = input.signing_deadline;
+            document.storage_uri = input.storage_uri;
+            document.category = input.category;
+            document.parent = input.parent;
+            document.bump = document_bump;
+            document.schema_version = CURRENT_SCHEMA_VERSION;
+            
+            let mut hash_index = Account::<HashIndex>::try_from_unchecked(hash_index_info)?;
+            hash_index.document = document.key();
+            hash_index.authority = document.authority;
+            hash_index.timestamp = now;
+            
+            authority_stats.document_count = authority_stats
+                .document_count
+                .checked_add(1)
+                .ok_or(DocumentError::CounterOverflow)?;
+            
+            emit!(DocumentRegistered {
+                document_id: document.key(),
+                authority: document.authority,
+                document_hash: document.document_hash.clone(),
+                hash_algorithm: document.hash_algorithm,
+                timestamp: document.created_at,
+                slot: document.slot,
+                expires_at: document.expires_at,
+                fee,
+                fee_mint: None,
+                storage_uri: document.storage_uri.clone(),
+                parent: document.parent,
+            });
+            
+            document.exit(&ID)?;
+            hash_index.exit(&ID)?;
+        }
+        
+        let document_manager = &mut ctx.accounts.document_manager;
+        document_manager.document_count = document_manager
+            .document_count
+            .checked_add(batch_size)
+            .ok_or(DocumentError::CounterOverflow)?;
+        document_manager.last_activity_ts = Clock::get()?.unix_timestamp;
+        
+        Ok(())
+    }
+    
+    /// Anchors many off-chain documents under a single Merkle root.
+    pub fn register_merkle_batch(
+        ctx: Context<RegisterMerkleBatch>,
+        merkle_root: [u8; 32],
+        leaf_count: u32,
+        description: String,
+    ) -> Result<()> {
+        require!(!ctx.accounts.document_manager.paused, DocumentError::ProgramPaused);
+        require!(leaf_count > 0, DocumentError::EmptyMerkleBatch);
+        require!(
+            leaf_count <= 1 << MAX_MERKLE_PROOF_LEN,
+            DocumentError::MerkleBatchTooLarge
+        );
+        require!(
+            description.len() <= MAX_MERKLE_DESCRIPTION_LEN,
+            DocumentError::DescriptionTooLong
+        );
+        
+        let merkle_batch = &mut ctx.accounts.merkle_batch;
+        let now = Clock::get()?.unix_timestamp;
+        merkle_batch.authority = ctx.accounts.authority.key();
+        merkle_batch.merkle_root = merkle_root;
+        merkle_batch.leaf_count = leaf_count;
+        merkle_batch.created_at = now;
+        merkle_batch.description = description;
+        
+        emit!(MerkleBatchRegistered {
+            merkle_batch: merkle_batch.key(),
+            authority: merkle_batch.authority,
+            merkle_root,
+            leaf_count,
+            description: merkle_batch.description.clone(),
+            timestamp: now,
+        });
+        
+        Ok(())
+    }
+    
+    /// Checks that `leaf` is included in the batch at position `index`; see
+    /// `merkle_root` for how the tree is built. Sets the return data to a
+    /// Borsh-encoded `true` on success.
+    pub fn verify_merkle_inclusion(
+        ctx: Context<VerifyMerkleInclusion>,
+        leaf: [u8; 32],
+        proof: Vec<[u8; 32]>,
+        index: u32,
+    ) -> Result<()> {
+        require!(proof.len() <= MAX_MERKLE_PROOF_LEN, DocumentError::ProofTooLong);
+        require!(
+            index < ctx.accounts.merkle_batch.leaf_count,
+            DocumentError::LeafIndexOutOfRange
+        );
+        
+        require!(
+            merkle_root(&leaf, index, &proof) == Some(ctx.accounts.merkle_batch.merkle_root),
+            DocumentError::InvalidProof
+        );
+        
+        set_return_data(&true.try_to_vec()?);
+        
+        Ok(())
+    }
+    
+    /// Moves a document registered with `requires_review` to `Active`.
+    pub fn approve_document(ctx: Context<ReviewDocument>) -> Result<()> {
+        ctx.accounts.document.require_mutable()?;
+        
+        let document = &mut ctx.accounts.document;
+        
+        document.require_reviewer(&ctx.accounts.reviewer.key())?;
+        
+        document.status = DocumentStatus::Active;
+        
+        emit!(DocumentApproved {
+            document_id: document.key(),
+            reviewer: ctx.accounts.reviewer.key(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+    
+    /// Moves a document registered with `requires_review` to `Rejected`.
+    pub fn reject_document(ctx: Context<ReviewDocument>, reason: String) -> Result<()> {
+        ctx.accounts.document.require_mutable()?;
+        
+        let document = &mut ctx.accounts.document;
+        
+        require!(
+            reason.len() <= MAX_REVOCATION_REASON_LEN,
+            DocumentError::ReasonTooLong
+        );
+        document.require_reviewer(&ctx.accounts.reviewer.key())?;
+        
+        document.status = DocumentStatus::Rejected;
+        document.rejection_reason = Some(reason.clone());
+        
+        emit!(DocumentRejected {
+            document_id: document.key(),
+            reviewer: ctx.accounts.reviewer.key(),
+            reason,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+    
+    /// Replaces the document hash. With `reset_signatures`, every approval
+    /// of the previous version must be passed in `remaining_accounts` as the
+    /// signature PDA followed by its signer; they are closed, refunding the
+    /// signers, so each can sign the new version and a signer sequence
+    /// restarts from its first signer.
+    pub fn update_document<'info>(
+        ctx: Context<'_, '_, '_, 'info, UpdateDocument<'info>>,
+        document_hash: String,
+        reset_signatures: bool,
+    ) -> Result<()> {
+        ctx.accounts.document.require_mutable()?;
+        require!(document_hash.len() <= MAX_HASH_LEN, DocumentError::HashTooLong);
+        
+        let document = &mut ctx.accounts.document;
+        let signer = ctx.accounts.authority.key();
+        let now = Clock::get()?.unix_timestamp;
+        
+        validate_hash(&document_hash, document.hash_algorithm)?;
+        require!(document.document_hash != document_hash, DocumentError::HashUnchanged);
+        
+        document.check_updater(&signer, ctx.accounts.delegation.as_ref())?;
+        document.require_active()?;
+        require!(!document.is_expired(now), DocumentError::DocumentExpired);
+        
+        let previous_hash = std::mem::replace(&mut document.document_hash, document_hash);
+        document.document_hash_bytes = decode_digest_32(&document.document_hash);
+        document.updated_at = now;
+        document.version += 1;
+        if reset_signatures {
+            // Existing signatures cover the previous version only
+            let pairs = ctx.remaining_accounts.chunks_exact(2);
+            require!(pairs.remainder().is_empty(), ErrorCode::AccountNotEnoughKeys);
+            for accounts in pairs {
+                let (signature_info, signer_info) = (&accounts[0], &accounts[1]);
+                let signature = Account::<DocumentSignature>::try_from(signature_info)?;
+                
+                require_keys_eq!(
+                    signature.document,
+                    document.key(),
+                    DocumentError::SignatureDocumentMismatch
+                );
+                require_keys_eq!(signature.signer, signer_info.key(), DocumentError::Unauthorized);
+                
+                if signature.kind == SignatureKind::Approve {
+                    document.release_approval(&signature.signer, signature.valid_until)?;
+                }
+                signature.close(signer_info.clone())?;
+            }
+            require!(
+                document.signatures_count == 0,
+                DocumentError::SignaturesNotReset
+            );
+            document.sequence_gaps = 0;
+            document.approval_expiries.clear();
+        }
+        
+        let document_version = &mut ctx.accounts.document_version;
+        document_version.document = document.key();
+        document_version.previous_hash = previous_hash;
+        document_version.hash = document.document_hash.clone();
+        document_version.version = document.version;
+        document_version.timestamp = now;
+        document_version.updated_by = signer;
+        
+        emit!(DocumentUpdated {
+            document_id: document.key(),
+            authority: document.authority,
+            document_hash: document.document_hash.clone(),
+            hash_algorithm: document.hash_algorithm,
+            version: document.version,
+            timestamp: now,
+        });
+        
+        emit!(VersionRecorded {
+            document_id: document.key(),
+            version_id: document_version.key(),
+            version: document_version.version,
+            previous_hash: document_version.previous_hash.clone(),
+            hash: document_version.hash.clone(),
+            updated_by: signer,
+            timestamp: now,
+        });
+        
+        Ok(())
+    }
+    
+    pub fn sign_document(
+        ctx: Context<SignDocument>,
+        signature_hash: String,
+        kind: SignatureKind,
+        role: Option<String>,
+        comment_hash: Option<String>,
+        valid_until: Option<i64>,
+    ) -> Result<()> {
+        record_signature(
+            ctx.accounts,
+            signature_hash,
+            kind,
+            role,
+            comment_hash,
+            valid_until,
+            false,
+            ctx.bumps["signature"],
+        )
+    }
+    
+    /// Like `sign_document`, but the transaction must also carry an ed25519
+    /// program instruction proving the signer signed the document hash.
+    pub fn sign_document_verified(
+        ctx: Context<SignDocumentVerified>,
+        signature_hash: String,
+        kind: SignatureKind,
+        role: Option<String>,
+        comment_hash: Option<String>,
+        valid_until: Option<i64>,
+    ) -> Result<()> {
+        verify_ed25519_instruction(
+            &ctx.accounts.instructions,
+            &ctx.accounts.sign.signer.key(),
+            ctx.accounts.sign.document.document_hash.as_bytes(),
+        )?;
+        
+        record_signature(
+            &mut ctx.accounts.sign,
+            signature_hash,
+            kind,
+            role,
+            comment_hash,
+            valid_until,
+            true,
+            ctx.bumps["signature"],
+        )
+    }
+    
+    /// Records an approval from each of up to `MAX_CO_SIGNERS` co-signers.
+    /// `remaining_accounts` holds each signer followed by their signature
+    /// PDA, in the same order as `signature_hashes`; every signer must sign
+    /// the transaction.
+    pub fn sign_document_multi<'info>(
+        ctx: Context<'_, '_, '_, 'info, SignDocumentMulti<'info>>,
+        signature_hashes: Vec<String>,
+    ) -> Result<()> {
+        require!(!ctx.accounts.document_manager.paused, DocumentError::ProgramPaused);
+        require!(
+            signature_hashes.len() <= MAX_CO_SIGNERS,
+            DocumentError::TooManyCoSigners
+        );
+        require!(
+            ctx.remaining_accounts.len() == signature_hashes.len() * 2,
+            ErrorCode::AccountNotEnoughKeys
+        );
+        
+        let payer = &ctx.accounts.payer;
+        let system_program = &ctx.accounts.system_program;
+        let document = &mut ctx.accounts.document;
+        let clock = Clock::get()?;
+        let now = clock.unix_timestamp;
+        
+        document.require_mutable()?;
+        document.require_active()?;
+        require!(!document.is_expired(now), DocumentError::DocumentExpired);
+        require!(
+            !document.signing_period_ended(now),
+            DocumentError::SigningPeriodEnded
+        );
+        
+        let mut signers = Vec::with_capacity(signature_hashes.len());
+        for (signature_hash, accounts) in signature_hashes
+            .into_iter()
+            .zip(ctx.remaining_accounts.chunks(2))
+        {
+            let (signer_info, signature_info) = (&accounts[0], &accounts[1]);
+            let signer = signer_info.key();
+            
+            require!(signer_info.is_signer, ErrorCode::AccountNotSigner);
+            require!(!signers.contains(&signer), DocumentError::DuplicateSigner);
+            signers.push(signer);
+            require!(
+                signature_hash.len() <= MAX_SIGNATURE_HASH_LEN,
+                DocumentError::SignatureHashTooLong
+            );
+            require!(
+                document.required_signers.is_empty() || document.required_signers.contains(&signer),
+                DocumentError::SignerNotAuthorized
+            );
+            // Only an account this program owns is a recorded signature; a
+            // pre-funded address is still created by `create_pda_account`
+            require!(signature_info.owner != &ID, DocumentError::AlreadySigned);
+            document.check_sequence(&signer)?;
+            
+            let signature_bump = create_pda_account(
+                payer,
+                signature_info,
+                system_program,
+                DocumentSignature::LEN,
+                &[b"signature", document.key().as_ref(), signer.as_ref()],
+            )?;
+            
+            let mut signature = Account::<DocumentSignature>::try_from_unchecked(signature_info)?;
+            signature.document = document.key();
+            signature.signer = signer;
+            signature.signature_hash = signature_hash;
+            signature.timestamp = now;
+            signature.kind = SignatureKind::Approve;
+            signature.document_version = document.version;
+            signature.slot = clock.slot;
+            signature.role = None;
+            signature.comment_hash = None;
+            signature.valid_until = None;
+            signature.bump = signature_bump;
+            signature.schema_version = CURRENT_SCHEMA_VERSION;
+            document.record_approval(&signer, None)?;
+            
+            emit!(DocumentSigned {
+                document_id: document.key(),
+                signer,
+                signature_id: signature.key(),
+                kind: signature.kind,
+                document_version: signature.document_version,
+                verified: false,
+                timestamp: now,
+                slot: clock.slot,
+                role: None,
+                comment_hash: None,
+            });
+            
+            signature.exit(&ID)?;
+        }
+        
+        let document_manager = &mut ctx.accounts.document_manager;
+        document_manager.total_signatures = document_manager
+            .total_signatures
+            .checked_add(signers.len() as u64)
+            .ok_or(DocumentError::CounterOverflow)?;
+        document_manager.last_activity_ts = now;
+        
+        mark_if_fully_executed(document, now)
+    }
+    
+    /// Sets up a concurrent Merkle tree to hold the document's compressed
+    /// signatures. The client allocates `merkle_tree`, owned by the account
+    /// compression program and sized for `max_depth` and `max_buffer_size`.
+    pub fn init_signature_tree(
+        ctx: Context<InitSignatureTree>,
+        max_depth: u32,
+        max_buffer_size: u32,
+    ) -> Result<()> {
+        ctx.accounts.document.require_mutable()?;
+        
+        let document = &mut ctx.accounts.document;
+        
+        require!(
+            document.authority == ctx.accounts.authority.key(),
+            DocumentError::Unauthorized
+        );
+        require!(
+            document.signature_tree.is_none(),
+            DocumentError::SignatureTreeExists
+        );
+        
+        let document_key = document.key();
+        spl_account_compression::cpi::init_empty_merkle_tree(
+            CpiContext::new_with_signer(
+                ctx.accounts.compression_program.to_account_info(),
+                spl_account_compression::cpi::accounts::Initialize {
+                    merkle_tree: ctx.accounts.merkle_tree.to_account_info(),
+                    authority: ctx.accounts.tree_authority.to_account_info(),
+                    noop: ctx.accounts.noop_program.to_account_info(),
+                },
+                &[&[
+                    b"tree-authority",
+                    document_key.as_ref(),
+                    &[ctx.bumps["tree_authority"]],
+                ]],
+            ),
+            max_depth,
+            max_buffer_size,
+        )?;
+        
+        document.signature_tree = Some(ctx.accounts.merkle_tree.key());
+        
+        Ok(())
+    }
+    
+    /// Appends the signer's approval as a leaf of the document's signature
+    /// tree instead of creating a signature account. Compressed signatures
+    /// are not deduplicated and do not count toward completion.
+    pub fn append_signature_leaf(
+        ctx: Context<AppendSignatureLeaf>,
+        signature_hash: String,
+    ) -> Result<()> {
+        require!(!ctx.accounts.document_manager.paused, DocumentError::ProgramPaused);
+        ctx.accounts.document.require_mutable()?;
+        require!(
+            signature_hash.len() <= MAX_SIGNATURE_HASH_LEN,
+            DocumentError::SignatureHashTooLong
+        );
+        
+        let document = &mut ctx.accounts.document;
+        let signer = ctx.accounts.signer.key();
+        let now = Clock::get()?.unix_timestamp;
+        
+        require!(
+            document.signature_tree == Some(ctx.accounts.merkle_tree.key()),
+            DocumentError::SignatureTreeMismatch
+        );
+        document.require_active()?;
+        require!(!document.is_expired(now), DocumentError::DocumentExpired);
+        require!(
+            !document.signing_period_ended(now),
+            DocumentError::SigningPeriodEnded
+        );
+        require!(
+            document.required_signers.is_empty() || document.required_signers.contains(&signer),
+            DocumentError::SignerNotAuthorized
+        );
+        
+        let document_key = document.key();
+        let leaf = compressed_signature_leaf(&document_key, &signer, &signature_hash, now);
+        spl_account_compression::cpi::append(
+            CpiContext::new_with_signer(
+                ctx.accounts.compression_program.to_account_info(),
+                spl_account_compression::cpi::accounts::Modify {
+                    merkle_tree: ctx.accounts.merkle_tree.to_account_info(),
+                    authority: ctx.accounts.tree_authority.to_account_info(),
+                    noop: ctx.accounts.noop_program.to_account_info(),
+                },
+                &[&[
+                    b"tree-authority",
+                    document_key.as_ref(),
+                    &[ctx.bumps["tree_authority"]],
+                ]],
+            ),
+            leaf,
+        )?;
+        
+        let leaf_index = document.compressed_signatures_count;
+        document.compressed_signatures_count = leaf_index
+            .checked_add(1)
+            .ok_or(DocumentError::CounterOverflow)?;
+        
+        emit!(CompressedSignatureAppended {
+            document_id: document_key,
+            signer,
+            signature_hash,
+            leaf,
+            leaf_index,
+            timestamp: now,
+        });
+        
+        Ok(())
+    }
+    
+    /// Succeeds only if the document's signature tree, at `root`, holds the
+    /// leaf for `signer`'s signature at `index`. The proof is passed as
+    /// `remaining_accounts`, one node per account key.
+    pub fn verify_signature_leaf<'info>(
+        ctx: Context<'_, '_, '_, 'info, VerifySignatureLeaf<'info>>,
+        root: [u8; 32],
+        signer: Pubkey,
+        signature_hash: String,
+        timestamp: i64,
+        index: u32,
+    ) -> Result<()> {
+        let document = &ctx.accounts.document;
+        
+        require!(
+            document.signature_tree == Some(ctx.accounts.merkle_tree.key()),
+            DocumentError::SignatureTreeMismatch
+        );
+        require!(
+            ctx.remaining_accounts.len() <= MAX_MERKLE_PROOF_LEN,
+            DocumentError::ProofTooLong
+        );
+        
+        let leaf = compressed_signature_leaf(&document.key(), &signer, &signature_hash, timestamp);
+        spl_account_compression::cpi::verify_leaf(
+            CpiContext::new(
+                ctx.accounts.compression_program.to_account_info(),
+                spl_account_compression::cpi::accounts::VerifyLeaf {
+                    merkle_tree: ctx.accounts.merkle_tree.to_account_info(),
+                },
+            )
+            .with_remaining_accounts(ctx.remaining_accounts.to_vec()),
+            root,
+            leaf,
+            index,
+        )
+    }
+    
+    /// Succeeds only if `signature` was made on the document's current version.
+    pub fn verify_signature_current(ctx: Context<VerifySignatureCurrent>) -> Result<()> {
+        let document = &ctx.accounts.document;
+        let signature = &ctx.accounts.signature;
+        
+        require_keys_eq!(
+            signature.document,
+            document.key(),
+            DocumentError::SignatureDocumentMismatch
+        );
+        require!(
+            signature.document_version == document.version,
+            DocumentError::SignatureOutdated
+        );
+        
+        Ok(())
+    }
+    
+    /// Sets the program return data to a Borsh-encoded `DocumentSummary` so
+    /// other programs can read a document through CPI.
+    pub fn get_document(ctx: Context<GetDocument>) -> Result<()> {
+        let document = &ctx.accounts.document;
+        
+        let summary = DocumentSummary {
+            document_hash: document.document_hash.clone(),
+            status: document.status,
+            version: document.version,
+            signatures_count: document.signatures_count,
+            notarizations_count: document.notarizations_count,
+        };
+        set_return_data(&summary.try_to_vec()?);
+        
+        Ok(())
+    }
+    
+    /// Sets the program return data to a Borsh-encoded `ManagerStats`.
+    pub fn get_manager_stats(ctx: Context<GetManagerStats>) -> Result<()> {
+        let document_manager = &ctx.accounts.document_manager;
+        
+        let stats = ManagerStats {
+            authority: document_manager.authority,
+            paused: document_manager.paused,
+            document_count: document_manager.document_count,
+            archived_count: document_manager.archived_count,
+            total_signatures: document_manager.total_signatures,
+            last_activity_ts: document_manager.last_activity_ts,
+        };
+        set_return_data(&stats.try_to_vec()?);
+        
+        Ok(())
+    }
+    
+    /// Sets the program return data to a Borsh-encoded `VerificationResult`
+    /// comparing the stored hash with `expected_hash`, and emits the outcome
+    /// as `DocumentVerified`. The document is not modified.
+    pub fn verify_document(ctx: Context<VerifyDocument>, expected_hash: String) -> Result<()> {
+        let document = &ctx.accounts.document;
+        
+        let result = VerificationResult {
+            matched: document.document_hash == expected_hash,
+            owner: document.authority,
+            timestamp: document.created_at,
+        };
+        set_return_data(&result.try_to_vec()?);
+        
+        emit!(DocumentVerified {
+            document_id: document.key(),
+            matched: result.matched,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+    
+    pub fn revoke_signature(ctx: Context<RevokeSignature>) -> Result<()> {
+        ctx.accounts.document.require_mutable()?;
+        
+        let document = &mut ctx.accounts.document;
+        let signature = &ctx.accounts.signature;
+        
+        require!(
+            document.status == DocumentStatus::Active,
+            DocumentError::SignatureLocked
+        );
+        
+        if signature.kind == SignatureKind::Approve {
+            // Only the latest signer in a sequence may withdraw; signers
+            // after them would otherwise have signed out of order
+            if !document.signer_sequence.is_empty() {
+                require!(
+                    document.sequence_position(&signature.signer)
+                        == document.latest_sequence_position(),
+                    DocumentError::OutOfOrderSignature
+                );
+            }
+            document.release_approval(&signature.signer, signature.valid_until)?;
+        }
+        
+        emit!(SignatureRevoked {
+            document_id: document.key(),
+            signer: signature.signer,
+            signature_id: signature.key(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+    
+    /// Closes expired signatures of the document, refunding each signer's
+    /// rent, so stale approvals stop counting toward completion.
+    /// `remaining_accounts` holds each signature PDA followed by its signer.
+    /// Anyone may call it.
+    pub fn prune_expired_signatures<'info>(
+        ctx: Context<'_, '_, '_, 'info, PruneExpiredSignatures<'info>>,
+    ) -> Result<()> {
+        ctx.accounts.document.require_mutable()?;
+        let pairs = ctx.remaining_accounts.chunks_exact(2);
+        require!(pairs.remainder().is_empty(), ErrorCode::AccountNotEnoughKeys);
+        
+        let document = &mut ctx.accounts.document;
+        let now = Clock::get()?.unix_timestamp;
+        
+        require!(
+            document.status == DocumentStatus::Active,
+            DocumentError::SignatureLocked
+        );
+        
+        for accounts in pairs {
+            let (signature_info, signer_info) = (&accounts[0], &accounts[1]);
+            let signature = Account::<DocumentSignature>::try_from(signature_info)?;
+            
+            require_keys_eq!(
+                signature.document,
+                document.key(),
+                DocumentError::SignatureDocumentMismatch
+            );
+            require_keys_eq!(signature.signer, signer_info.key(), DocumentError::Unauthorized);
+            require!(
+                !signature.is_signature_valid(now),
+                DocumentError::SignatureStillValid
+            );
+            
+            // An expired approval anywhere in a sequence may go; its signer
+            // becomes the next one due to sign
+            if signature.kind == SignatureKind::Approve {
+                document.release_approval(&signature.signer, signature.valid_until)?;
+            }
+            
+            emit!(SignatureExpired {
+                document_id: document.key(),
+                signer: signature.signer,
+                signature_id: signature.key(),
+                timestamp: now,
+            });
+            
+            signature.close(signer_info.clone())?;
+        }
+        
+        Ok(())
+    }
+    
+    /// Invites `signer` to sign the document before `deadline`. The request
+    /// is marked fulfilled when they sign through `sign_document` with it.
+    pub fn request_signature(
+        ctx: Context<RequestSignature>,
+        signer: Pubkey,
+        deadline: i64,
+    ) -> Result<()> {
+        ctx.accounts.document.require_mutable()?;
+        
+        let document = &ctx.accounts.document;
+        let now = Clock::get()?.unix_timestamp;
+        
+        require!(
+            document.authority == ctx.accounts.authority.key(),
+            DocumentError::Unauthorized
+        );
+        document.require_active()?;
+        require!(deadline > now, DocumentError::InvalidDeadline);
+        
+        let signature_request = &mut ctx.accounts.signature_request;
+        signature_request.document = document.key();
+        signature_request.signer = signer;
+        signature_request.requested_by = ctx.accounts.authority.key();
+        signature_request.deadline = deadline;
+        signature_request.status = SignatureRequestStatus::Pending;
+        signature_request.reason_hash = None;
+        signature_request.created_at = now;
+        
+        emit!(SignatureRequested {
+            document_id: document.key(),
+            request_id: signature_request.key(),
+            signer,
+            requested_by: signature_request.requested_by,
+            deadline,
+            timestamp: now,
+        });
+        
+        Ok(())
+    }
+    
+    /// Lets the invited signer turn down a pending request.
+    pub fn decline_signature(ctx: Context<DeclineSignature>, reason_hash: String) -> Result<()> {
+        let signature_request = &mut ctx.accounts.signature_request;
+        let now = Clock::get()?.unix_timestamp;
+        
+        require!(
+            reason_hash.len() <= MAX_REASON_HASH_LEN,
+            DocumentError::ReasonHashTooLong
+        );
+        require!(
+            signature_request.status == SignatureRequestStatus::Pending,
+            DocumentError::SignatureRequestNotPending
+        );
+        require!(
+            now < signature_request.deadline,
+            DocumentError::SignatureRequestPastDeadline
+        );
+        
+        signature_request.status = SignatureRequestStatus::Declined;
+        signature_request.reason_hash = Some(reason_hash);
+        
+        emit!(SignatureDeclined {
+            document_id: signature_request.document,
+            request_id: signature_request.key(),
+            signer: signature_request.signer,
+            reason_hash: signature_request.reason_hash.clone(),
+            timestamp: now,
+        });
+        
+        Ok(())
+    }
+    
+    /// Returns the rent of a request whose deadline has passed to the
+    /// authority that made it.
+    pub fn close_signature_request(ctx: Context<CloseSignatureRequest>) -> Result<()> {
+        let signature_request = &ctx.accounts.signature_request;
+        let now = Clock::get()?.unix_timestamp;
+        
+        require!(
+            signature_request.requested_by == ctx.accounts.authority.key(),
+            DocumentError::Unauthorized
+        );
+        require!(
+            now >= signature_request.deadline,
+            DocumentError::SignatureRequestNotExpired
+        );
+        
+        emit!(SignatureRequestExpired {
+            document_id: signature_request.document,
+            request_id: signature_request.key(),
+            signer: signature_request.signer,
+            status: signature_request.status,
+            timestamp: now,
+        });
+        
+        Ok(())
+    }
+    
+    /// Records a registered notary's seal on a document.
+    pub fn notarize_document(
+        ctx: Context<NotarizeDocument>,
+        seal_hash: String,
+        jurisdiction: Option<String>,
+    ) -> Result<()> {
+        ctx.accounts.document.require_mutable()?;
+        require!(
+            seal_hash.len() <= MAX_SIGNATURE_HASH_LEN,
+            DocumentError::SignatureHashTooLong
+        );
+        require!(
+            jurisdiction.as_ref().map_or(0, String::len) <= MAX_JURISDICTION_LEN,
+            DocumentError::JurisdictionTooLong
+        );
+        require!(ctx.accounts.notary_entry.active, DocumentError::NotaryNotActive);
+        
+        let document = &mut ctx.accounts.document;
+        require!(
+            document.status != DocumentStatus::Revoked,
+            DocumentError::DocumentRevoked
+        );
+        
+        let notarization = &mut ctx.accounts.notarization;
+        notarization.document = document.key();
+        notarization.notary = ctx.accounts.notary.key();
+        notarization.seal_hash = seal_hash;
+        notarization.timestamp = Clock::get()?.unix_timestamp;
+        notarization.jurisdiction = jurisdiction;
+        document.notarizations_count = document
+            .notarizations_count
+            .checked_add(1)
+            .ok_or(DocumentError::CounterOverflow)?;
+        
+        emit!(DocumentNotarized {
+            document_id: document.key(),
+            notary: notarization.notary,
+            notarization_id: notarization.key(),
+            seal_hash: notarization.seal_hash.clone(),
+            jurisdiction: notarization.jurisdiction.clone(),
+            timestamp: notarization.timestamp,
+        });
+        
+        Ok(())
+    }
+    
+    /// Sets the return data to a Borsh-encoded `bool`: whether `notary` has
+    /// notarized `document`.
+    pub fn has_notarization(ctx: Context<HasNotarization>) -> Result<()> {
+        let notarization = &ctx.accounts.notarization;
+        let notarized = notarization.owner == &ID && !notarization.data_is_empty();
+        
+        set_return_data(&notarized.try_to_vec()?);
+        
+        Ok(())
+    }
+    
+    /// Attaches a third-party statement to a document without counting the
+    /// attester as a signatory. Accepted in any document status, archived
+    /// documents included, but not under a legal hold.
+    pub fn attest_document(
+        ctx: Context<AttestDocument>,
+        attestation_type: u8,
+        statement_hash: String,
+    ) -> Result<()> {
+        require!(!ctx.accounts.document.legal_hold, DocumentError::LegalHold);
+        require!(
+            statement_hash.len() <= MAX_STATEMENT_HASH_LEN,
+            DocumentError::StatementHashTooLong
+        );
+        
+        let attestation = &mut ctx.accounts.attestation;
+        attestation.document = ctx.accounts.document.key();
+        attestation.attester = ctx.accounts.attester.key();
+        attestation.attestation_type = attestation_type;
+        attestation.statement_hash = statement_hash;
+        attestation.timestamp = Clock::get()?.unix_timestamp;
+        
+        emit!(DocumentAttested {
+            document_id: attestation.document,
+            attester: attestation.attester,
+            attestation_id: attestation.key(),
+            attestation_type,
+            statement_hash: attestation.statement_hash.clone(),
+            timestamp: attestation.timestamp,
+        });
+        
+        Ok(())
+    }
+    
+    pub fn revoke_attestation(ctx: Context<RevokeAttestation>) -> Result<()> {
+        let attestation = &ctx.accounts.attestation;
+        
+        emit!(AttestationRevoked {
+            document_id: attestation.document,
+            attester: attestation.attester,
+            attestation_id: attestation.key(),
+            attestation_type: attestation.attestation_type,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+    
+    /// Flags a document as disputed. Only parties holding a signature on the
+    /// document may raise a dispute, and only one can be open at a time.
+    pub fn raise_dispute(ctx: Context<RaiseDispute>, reason_hash: String) -> Result<()> {
+        require!(!ctx.accounts.document.legal_hold, DocumentError::LegalHold);
+        require!(
+            reason_hash.len() <= MAX_REASON_HASH_LEN,
+            DocumentError::ReasonHashTooLong
+        );
+        
+        let document = &mut ctx.accounts.document;
+        require!(!document.disputed, DocumentError::DocumentDisputed);
+        
+        let dispute = &mut ctx.accounts.dispute;
+        dispute.document = document.key();
+        dispute.raised_by = ctx.accounts.signer.key();
+        dispute.reason_hash = reason_hash;
+        dispute.raised_at = Clock::get()?.unix_timestamp;
+        document.disputed = true;
+        
+        emit!(DisputeRaised {
+            document_id: document.key(),
+            raised_by: dispute.raised_by,
+            reason_hash: dispute.reason_hash.clone(),
+            timestamp: dispute.raised_at,
+        });
+        
+        Ok(())
+    }
+    
+    /// Closes the open dispute on a document. Callable by the document
+    /// authority or the manager authority.
+    pub fn resolve_dispute(
+        ctx: Context<ResolveDispute>,
+        resolution: DisputeResolution,
+    ) -> Result<()> {
+        require!(!ctx.accounts.document.legal_hold, DocumentError::LegalHold);
+        
+        let authority = ctx.accounts.authority.key();
+        let document = &mut ctx.accounts.document;
+        
+        require!(
+            authority == document.authority
+                || authority == ctx.accounts.document_manager.authority,
+            DocumentError::Unauthorized
+        );
+        
+        document.disputed = false;
+        
+        emit!(DisputeResolved {
+            document_id: document.key(),
+            raised_by: ctx.accounts.dispute.raised_by,
+            resolved_by: authority,
+            resolution,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+    
+    /// Freezes a document: every mutating instruction fails with `LegalHold`
+    /// until the hold is released. Reserved to the manager authority, so the
+    /// document authority cannot lift it.
+    pub fn place_legal_hold(ctx: Context<SetLegalHold>) -> Result<()> {
+        require!(
+            ctx.accounts.document_manager.authority == ctx.accounts.authority.key(),
+            DocumentError::Unauthorized
+        );
+        
+        let document = &mut ctx.accounts.document;
+        require!(!document.legal_hold, DocumentError::LegalHold);
+        
+        let now = Clock::get()?.unix_timestamp;
+        document.legal_hold = true;
+        document.legal_hold_by = Some(ctx.accounts.authority.key());
+        document.legal_hold_at = Some(now);
+        
+        emit!(LegalHoldPlaced {
+            document_id: document.key(),
+            authority: ctx.accounts.authority.key(),
+            timestamp: now,
+        });
+        
+        Ok(())
+    }
+    
+    pub fn release_legal_hold(ctx: Context<SetLegalHold>) -> Result<()> {
+        require!(
+            ctx.accounts.document_manager.authority == ctx.accounts.authority.key(),
+            DocumentError::Unauthorized
+        );
+        
+        let document = &mut ctx.accounts.document;
+        require!(document.legal_hold, DocumentError::NotOnLegalHold);
+        
+        document.legal_hold = false;
+        document.legal_hold_by = None;
+        document.legal_hold_at = None;
+        
+        emit!(LegalHoldReleased {
+            document_id: document.key(),
+            authority: ctx.accounts.authority.key(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+    
+    /// Temporarily blocks updates, signatures and transfers without changing
+    /// the document's status.
+    pub fn lock_document(ctx: Context<LockDocument>) -> Result<()> {
+        require!(!ctx.accounts.document.legal_hold, DocumentError::LegalHold);
+        
+        let document = &mut ctx.accounts.document;
+        
+        require!(
+            document.authority == ctx.accounts.authority.key(),
+            DocumentError::Unauthorized
+        );
+        require!(!document.locked, DocumentError::DocumentLocked);
+        
+        document.locked = true;
+        
+        emit!(DocumentLocked {
+            document_id: document.key(),
+            authority: document.authority,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+    
+    pub fn unlock_document(ctx: Context<LockDocument>) -> Result<()> {
+        require!(!ctx.accounts.document.legal_hold, DocumentError::LegalHold);
+        
+        let document = &mut ctx.accounts.document;
+        
+        require!(
+            document.authority == ctx.accounts.authority.key(),
+            DocumentError::Unauthorized
+        );
+        require!(document.locked, DocumentError::DocumentNotLocked);
+        
+        document.locked = false;
+        
+        emit!(DocumentUnlocked {
+            document_id: document.key(),
+            authority: document.authority,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+    
+    /// Records that `source` stands in `relation` to `target`.
+    pub fn link_documents(ctx: Context<LinkDocuments>, relation: DocumentRelation) -> Result<()> {
+        ctx.accounts.source.require_mutable()?;
+        
+        let source = &ctx.accounts.source;
+        let target = &ctx.accounts.target;
+        
+        require!(
+            source.authority == ctx.accounts.authority.key(),
+            DocumentError::Unauthorized
+        );
+        require!(
+            target.status != DocumentStatus::Revoked,
+            DocumentError::DocumentRevoked
+        );
+        
+        let link = &mut ctx.accounts.link;
+        let now = Clock::get()?.unix_timestamp;
+        link.source = source.key();
+        link.target = target.key();
+        link.relation = relation;
+        link.created_at = now;
+        
+        emit!(DocumentsLinked {
+            link_id: link.key(),
+            source: link.source,
+            target: link.target,
+            relation,
+            timestamp: now,
+        });
+        
+        Ok(())
+    }
+    
+    pub fn unlink_documents(ctx: Context<UnlinkDocuments>) -> Result<()> {
+        ctx.accounts.source.require_mutable()?;
+        
+        let link = &ctx.accounts.link;
+        
+        require!(
+            ctx.accounts.source.authority == ctx.accounts.authority.key(),
+            DocumentError::Unauthorized
+        );
+        
+        emit!(DocumentsUnlinked {
+            link_id: link.key(),
+            source: link.source,
+            target: link.target,
+            relation: link.relation,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+    
+    pub fn archive_document(ctx: Context<ArchiveDocument>) -> Result<()> {
+        ctx.accounts.document.require_mutable()?;
+        
+        let document = &mut ctx.accounts.document;
+        
+        // Organization-owned documents can only be archived by an admin
+        match &ctx.accounts.organization {
+            Some(organization) if organization.key() == document.authority => check_org_role(
+                organization,
+                ctx.accounts.org_member.as_ref(),
+                &ctx.accounts.authority.key(),
+                OrgRole::Admin,
+            )?,
+            _ => check_acting_for(
+                &document.authority,
+                &ctx.accounts.authority.key(),
+                ctx.accounts.delegation.as_ref(),
+                DelegationScope::Archive,
+            )?,
+        }
+        require!(
+            document.status != DocumentStatus::Revoked,
+            DocumentError::DocumentRevoked
+        );
+        require!(
+            document.status != DocumentStatus::Archived,
+            DocumentError::AlreadyArchived
+        );
+        
+        document.status = DocumentStatus::Archived;
+        
+        let now = Clock::get()?.unix_timestamp;
+        let document_manager = &mut ctx.accounts.document_manager;
+        document_manager.archived_count = document_manager
+            .archived_count
+            .checked_add(1)
+            .ok_or(DocumentError::CounterOverflow)?;
+        document_manager.last_activity_ts = now;
+        
+        emit!(DocumentArchived {
+            document_id: document.key(),
+            authority: document.authority,
+            timestamp: now,
+        });
+        
+        Ok(())
+    }
+    
+    pub fn create_delegation(
+        ctx: Context<CreateDelegation>,
+        delegate: Pubkey,
+        expires_at: i64,
+        scope: DelegationScope,
+    ) -> Result<()> {
+        let delegation = &mut ctx.accounts.delegation;
+        let now = Clock::get()?.unix_timestamp;
+        
+        require!(expires_at > now, DocumentError::DelegationExpired);
+        
+        delegation.authority = ctx.accounts.authority.key();
+        delegation.delegate = delegate;
+        delegation.expires_at = expires_at;
+        delegation.scope = scope;
+        
+        emit!(DelegationCreated {
+            delegation_id: delegation.key(),
+            authority: delegation.authority,
+            delegate,
+            expires_at,
+            scope,
+            timestamp: now,
+        });
+        
+        Ok(())
+    }
+    
+    pub fn revoke_delegation(ctx: Context<RevokeDelegation>) -> Result<()> {
+        let delegation = &ctx.accounts.delegation;
+        
+        emit!(DelegationRevoked {
+            delegation_id: delegation.key(),
+            authority: delegation.authority,
+            delegate: delegation.delegate,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+    
+    pub fn unarchive_document(ctx: Context<UnarchiveDocument>) -> Result<()> {
+        ctx.accounts.document.require_mutable()?;
+        
+        let document = &mut ctx.accounts.document;
+        
+        require!(
+            document.authority == ctx.accounts.authority.key(),
+            DocumentError::Unauthorized
+        );
+        require!(
+            document.status != DocumentStatus::Revoked,
+            DocumentError::CannotUnarchiveRevoked
+        );
+        require!(
+            document.status == DocumentStatus::Archived,
+            DocumentError::DocumentNotArchived
+        );
+        
+        document.status = DocumentStatus::Active;
+        
+        emit!(DocumentUnarchived {
+            document_id: document.key(),
+            authority: document.authority,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+    
+    pub fn transfer_ownership(ctx: Context<TransferOwnership>) -> Result<()> {
+        ctx.accounts.document.require_mutable()?;
+        
+        let document = &mut ctx.accounts.document;
+        let new_authority = ctx.accounts.new_authority.key();
+        
+        // Organization-owned documents can only be transferred by an admin
+        match &ctx.accounts.organization {
+            Some(organization) if organization.key() == document.authority => check_org_role(
+                organization,
+                ctx.accounts.org_member.as_ref(),
+                &ctx.accounts.authority.key(),
+                OrgRole::Admin,
+            )?,
+            // An approved operator may transfer on the authority's behalf
+            _ => require!(
+                document.authority == ctx.accounts.authority.key()
+                    || document.approved_operator == Some(ctx.accounts.authority.key()),
+                DocumentError::Unauthorized
+            ),
+        }
+        require!(
+            document.status == DocumentStatus::Active,
+            DocumentError::DocumentNotActive
+        );
+        require!(
+            new_authority != document.authority,
+            DocumentError::SelfTransfer
+        );
+        require!(!document.disputed, DocumentError::DocumentDisputed);
+        
+        let old_owner = document.transfer_to(new_authority);
+        
+        emit!(OwnershipTransferred {
+            document_id: document.key(),
+            old_owner,
+            new_owner: new_authority,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+    
+    pub fn propose_transfer(ctx: Context<ProposeTransfer>, new_owner: Pubkey) -> Result<()> {
+        ctx.accounts.document.require_mutable()?;
+        
+        let document = &mut ctx.accounts.document;
+        
+        match &ctx.accounts.organization {
+            Some(organization) if organization.key() == document.authority => check_org_role(
+                organization,
+                ctx.accounts.org_member.as_ref(),
+                &ctx.accounts.authority.key(),
+                OrgRole::Admin,
+            )?,
+            _ => require!(
+                document.authority == ctx.accounts.authority.key(),
+                DocumentError::Unauthorized
+            ),
+        }
+        require!(
+            document.status == DocumentStatus::Active,
+            DocumentError::DocumentNotActive
+        );
+        require!(new_owner != document.authority, DocumentError::SelfTransfer);
+        
+        document.pending_owner = Some(new_owner);
+        
+        emit!(TransferProposed {
+            document_id: document.key(),
+            authority: document.authority,
+            pending_owner: new_owner,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+    
+    pub fn accept_transfer(ctx: Context<AcceptTransfer>) -> Result<()> {
+        ctx.accounts.document.require_mutable()?;
+        
+        let document = &mut ctx.accounts.document;
+        let new_owner = ctx.accounts.new_owner.key();
+        
+        let pending_owner = document
+            .pending_owner
+            .ok_or(DocumentError::NoPendingTransfer)?;
+        require!(pending_owner == new_owner, DocumentError::Unauthorized);
+        require!(
+            document.status == DocumentStatus::Active,
+            DocumentError::DocumentNotActive
+        );
+        require!(!document.disputed, DocumentError::DocumentDisputed);
+        
+        let old_owner = document.transfer_to(new_owner);
+        
+        emit!(TransferAccepted {
+            document_id: document.key(),
+            old_owner,
+            new_owner,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+    
+    pub fn cancel_transfer(ctx: Context<CancelTransfer>) -> Result<()> {
+        ctx.accounts.document.require_mutable()?;
+        
+        let document = &mut ctx.accounts.document;
+        
+        require!(
+            document.authority == ctx.accounts.authority.key(),
+            DocumentError::Unauthorized
+        );
+        let pending_owner = document
+            .pending_owner
+            .take()
+            .ok_or(DocumentError::NoPendingTransfer)?;
+        
+        emit!(TransferCancelled {
+            document_id: document.key(),
+            authority: document.authority,
+            pending_owner,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+    
+    /// Lets `operator` execute `transfer_ownership` for the authority until
+    /// the approval is revoked or the document changes hands.
+    pub fn approve_operator(ctx: Context<SetOperator>, operator: Pubkey) -> Result<()> {
+        ctx.accounts.document.require_mutable()?;
+        
+        let document = &mut ctx.accounts.document;
+        
+        require!(
+            document.authority == ctx.accounts.authority.key(),
+            DocumentError::Unauthorized
+        );
+        
+        document.approved_operator = Some(operator);
+        
+        emit!(OperatorApproved {
+            document_id: document.key(),
+            authority: document.authority,
+            operator,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+    
+    pub fn revoke_approval(ctx: Context<SetOperator>) -> Result<()> {
+        ctx.accounts.document.require_mutable()?;
+        
+        let document = &mut ctx.accounts.document;
+        
+        require!(
+            document.authority == ctx.accounts.authority.key(),
+            DocumentError::Unauthorized
+        );
+        let operator = document
+            .approved_operator
+            .take()
+            .ok_or(DocumentError::NoApprovedOperator)?;
+        
+        emit!(ApprovalRevoked {
+            document_id: document.key(),
+            authority: document.authority,
+            operator,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+    
+    pub fn set_delegate(ctx: Context<SetDelegate>, delegate: Option<Pubkey>) -> Result<()> {
+        ctx.accounts.document.require_mutable()?;
+        
+        let document = &mut ctx.accounts.document;
+        let timestamp = Clock::get()?.unix_timestamp;
+        
+        require!(
+            document.authority == ctx.accounts.authority.key(),
+            DocumentError::Unauthorized
+        );
+        
+        let previous = std::mem::replace(&mut document.delegate, delegate);
+        
+        if let Some(revoked) = previous.filter(|previous| Some(*previous) != delegate) {
+            emit!(DelegateRevoked {
+                document_id: document.key(),
+                authority: document.authority,
+                delegate: revoked,
+                timestamp,
+            });
+        }
+        if let Some(delegate) = delegate {
+            emit!(DelegateSet {
+                document_id: document.key(),
+                authority: document.authority,
+                delegate,
+                timestamp,
+            });
+        }
+        
+        Ok(())
+    }
+    
+    pub fn set_signature_threshold(
+        ctx: Context<SetSignatureThreshold>,
+        signature_threshold: u8,
+    ) -> Result<()> {
+        ctx.accounts.document.require_mutable()?;
+        
+        let document = &mut ctx.accounts.document;
+        
+        require!(
+            document.authority == ctx.accounts.authority.key(),
+            DocumentError::Unauthorized
+        );
+        document.require_active()?;
+        
+        document.set_signature_threshold(signature_threshold)
+    }
+    
+    pub fn update_structured_metadata(
+        ctx: Context<UpdateStructuredMetadata>,
+        metadata: DocumentMetadata,
+    ) -> Result<()> {
+        ctx.accounts.document.require_mutable()?;
+        
+        let document = &mut ctx.accounts.document;
+        
+        document.check_updater(&ctx.accounts.authority.key(), ctx.accounts.delegation.as_ref())?;
+        document.require_active()?;
+        metadata.validate()?;
+        
+        document.metadata = Some(metadata);
+        document.updated_at = Clock::get()?.unix_timestamp;
+        
+        Ok(())
+    }
+    
+    /// Corrects the document name. Unlike `update_document` this is not a
+    /// content revision, so the version and hash are left alone.
+    pub fn rename_document(ctx: Context<RenameDocument>, document_name: String) -> Result<()> {
+        ctx.accounts.document.require_mutable()?;
+        require!(document_name.len() <= MAX_NAME_LEN, DocumentError::NameTooLong);
+        
+        let document = &mut ctx.accounts.document;
+        let now = Clock::get()?.unix_timestamp;
+        
+        require!(
+            document.authority == ctx.accounts.authority.key(),
+            DocumentError::Unauthorized
+        );
+        document.require_active()?;
+        
+        let old_name = std::mem::replace(&mut document.document_name, document_name);
+        document.updated_at = now;
+        
+        emit!(DocumentRenamed {
+            document_id: document.key(),
+            old_name,
+            new_name: document.document_name.clone(),
+            timestamp: now,
+        });
+        
+        Ok(())
+    }
+    
+    /// Files the document under the passed category, or clears its category
+    /// when none is passed.
+    pub fn recategorize_document(ctx: Context<RecategorizeDocument>) -> Result<()> {
+        ctx.accounts.document.require_mutable()?;
+        
+        let document = &mut ctx.accounts.document;
+        
+        require!(
+            document.authority == ctx.accounts.authority.key(),
+            DocumentError::Unauthorized
+        );
+        let new_category = match &ctx.accounts.category {
+            Some(category) => Some(Category::check(category)?),
+            None => None,
+        };
+        
+        let old_category = document.category;
+        document.category = new_category;
+        
+        emit!(DocumentRecategorized {
+            document_id: document.key(),
+            old_category,
+            new_category,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+    
+    /// Records that `grantee` may view the document, optionally until
+    /// `expires_at`.
+    pub fn grant_access(
+        ctx: Context<GrantAccess>,
+        grantee: Pubkey,
+        expires_at: Option<i64>,
+        wrapped_key: Option<Vec<u8>>,
+    ) -> Result<()> {
+        ctx.accounts.document.require_mutable()?;
+        require!(
+            wrapped_key.as_ref().map_or(0, Vec::len) <= MAX_ENCRYPTED_KEY_LEN,
+            DocumentError::KeyTooLarge
+        );
+        
+        let document = &ctx.accounts.document;
+        
+        require!(
+            document.authority == ctx.accounts.authority.key(),
+            DocumentError::Unauthorized
+        );
+        
+        let grant = &mut ctx.accounts.grant;
+        let now = Clock::get()?.unix_timestamp;
+        grant.document = document.key();
+        grant.grantee = grantee;
+        grant.granted_at = now;
+        grant.expires_at = expires_at;
+        grant.wrapped_key = wrapped_key;
+        
+        emit!(AccessGranted {
+            document_id: document.key(),
+            grantee,
+            expires_at,
+            timestamp: now,
+        });
+        
+        Ok(())
+    }
+    
+    pub fn revoke_access(ctx: Context<RevokeAccess>) -> Result<()> {
+        ctx.accounts.document.require_mutable()?;
+        require!(
+            ctx.accounts.document.authority == ctx.accounts.authority.key(),
+            DocumentError::Unauthorized
+        );
+        
+        emit!(AccessRevoked {
+            document_id: ctx.accounts.document.key(),
+            grantee: ctx.accounts.grant.grantee,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+    
+    /// Stores, replaces or clears the document's content key, wrapped for
+    /// the authority.
+    pub fn set_encrypted_key(
+        ctx: Context<SetEncryptedKey>,
+        encrypted_key: Option<Vec<u8>>,
+    ) -> Result<()> {
+        ctx.accounts.document.require_mutable()?;
+        require!(
+            encrypted_key.as_ref().map_or(0, Vec::len) <= MAX_ENCRYPTED_KEY_LEN,
+            DocumentError::KeyTooLarge
+        );
+        
+        let document = &mut ctx.accounts.document;
+        
+        require!(
+            document.authority == ctx.accounts.authority.key(),
+            DocumentError::Unauthorized
+        );
+        
+        document.encrypted_key = encrypted_key;
+        
+        emit!(EncryptedKeyUpdated {
+            document_id: document.key(),
+            grantee: None,
+            cleared: document.encrypted_key.is_none(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+    
+    /// Stores, replaces or clears the content key wrapped for a grantee.
+    pub fn set_wrapped_key(
+        ctx: Context<SetWrappedKey>,
+        wrapped_key: Option<Vec<u8>>,
+    ) -> Result<()> {
+        ctx.accounts.document.require_mutable()?;
+        require!(
+            wrapped_key.as_ref().map_or(0, Vec::len) <= MAX_ENCRYPTED_KEY_LEN,
+            DocumentError::KeyTooLarge
+        );
+        require!(
+            ctx.accounts.document.authority == ctx.accounts.authority.key(),
+            DocumentError::Unauthorized
+        );
+        
+        let grant = &mut ctx.accounts.grant;
+        grant.wrapped_key = wrapped_key;
+        
+        emit!(EncryptedKeyUpdated {
+            document_id: grant.document,
+            grantee: Some(grant.grantee),
+            cleared: grant.wrapped_key.is_none(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+    
+    /// Creates or overwrites the attribute `key` of the document.
+    pub fn set_attribute(ctx: Context<SetAttribute>, key: String, value: String) -> Result<()> {
+        ctx.accounts.document.require_mutable()?;
+        
+        let document = &ctx.accounts.document;
+        
+        require!(
+            document.authority == ctx.accounts.authority.key(),
+            DocumentError::Unauthorized
+        );
+        require!(key.len() <= MAX_ATTRIBUTE_KEY_LEN, DocumentError::AttributeKeyTooLong);
+        require!(
+            value.len() <= MAX_ATTRIBUTE_VALUE_LEN,
+            DocumentError::AttributeValueTooLong
+        );
+        
+        let attribute = &mut ctx.accounts.attribute;
+        attribute.document = document.key();
+        attribute.key = key;
+        attribute.value = value;
+        
+        emit!(AttributeSet {
+            document_id: document.key(),
+            key: attribute.key.clone(),
+            value: attribute.value.clone(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+    
+    pub fn remove_attribute(ctx: Context<RemoveAttribute>) -> Result<()> {
+        ctx.accounts.document.require_mutable()?;
+        require!(
+            ctx.accounts.document.authority == ctx.accounts.authority.key(),
+            DocumentError::Unauthorized
+        );
+        
+        emit!(AttributeRemoved {
+            document_id: ctx.accounts.document.key(),
+            key: ctx.accounts.attribute.key.clone(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+    
+    /// Indexes the document under `tag`, so clients can list every document
+    /// carrying a tag.
+    pub fn add_tag(ctx: Context<AddTag>, tag: String) -> Result<()> {
+        ctx.accounts.document.require_mutable()?;
+        require!(tag.len() <= MAX_TAG_LEN, DocumentError::TagTooLong);
+        
+        let document = &mut ctx.accounts.document;
+        let now = Clock::get()?.unix_timestamp;
+        
+        require!(
+            document.authority == ctx.accounts.authority.key(),
+            DocumentError::Unauthorized
+        );
+        require!(
+            document.tags_count < MAX_DOCUMENT_TAGS,
+            DocumentError::TooManyDocumentTags
+        );
+        
+        document.tags_count += 1;
+        
+        let document_tag = &mut ctx.accounts.document_tag;
+        document_tag.tag_hash = hash(tag.as_bytes()).to_bytes();
+        document_tag.document = document.key();
+        document_tag.tag = tag;
+        document_tag.created_at = now;
+        
+        emit!(TagAdded {
+            document_id: document.key(),
+            tag: document_tag.tag.clone(),
+            timestamp: now,
+        });
+        
+        Ok(())
+    }
+    
+    pub fn remove_tag(ctx: Context<RemoveTag>) -> Result<()> {
+        ctx.accounts.document.require_mutable()?;
+        
+        let document = &mut ctx.accounts.document;
+        
+        require!(
+            document.authority == ctx.accounts.authority.key(),
+            DocumentError::Unauthorized
+        );
+        
+        document.tags_count = document.tags_count.saturating_sub(1);
+        
+        emit!(TagRemoved {
+            document_id: document.key(),
+            tag: ctx.accounts.document_tag.tag.clone(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+    
+    /// Points the document at its off-chain copy, or clears the pointer.
+    pub fn set_storage_uri(
+        ctx: Context<SetStorageUri>,
+        storage_uri: Option<String>,
+    ) -> Result<()> {
+        ctx.accounts.document.require_mutable()?;
+        
+        let document = &mut ctx.accounts.document;
+        
+        require!(
+            document.authority == ctx.accounts.authority.key(),
+            DocumentError::Unauthorized
+        );
+        if let Some(storage_uri) = &storage_uri {
+            validate_storage_uri(storage_uri)?;
+        }
+        
+        document.storage_uri = storage_uri;
+        
+        emit!(StorageUriUpdated {
+            document_id: document.key(),
+            authority: document.authority,
+            storage_uri: document.storage_uri.clone(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+    
+    /// Kept for existing clients; behaves as `extend_expiry`, so clearing
+    /// or shortening the expiry fails with `ExpiryNotExtended`.
+    pub fn set_expiry(ctx: Context<SetExpiry>, expires_at: Option<i64>) -> Result<()> {
+        ctx.accounts.document.require_mutable()?;
+        
+        let document = &mut ctx.accounts.document;
+        
+        require!(
+            document.authority == ctx.accounts.authority.key(),
+            DocumentError::Unauthorized
+        );
+        
+        document.extend_expiry(expires_at.ok_or(DocumentError::ExpiryNotExtended)?)
+    }
+    
+    pub fn extend_expiry(ctx: Context<ExtendExpiry>, expires_at: i64) -> Result<()> {
+        ctx.accounts.document.require_mutable()?;
+        
+        let document = &mut ctx.accounts.document;
+        
+        require!(
+            document.authority == ctx.accounts.authority.key(),
+            DocumentError::Unauthorized
+        );
+        
+        document.extend_expiry(expires_at)
+    }
+    
+    /// Moves a document whose signing deadline has passed to `Expired`.
+    /// Anyone may call it, so a keeper can run it without the authority key.
+    pub fn crank_expire(ctx: Context<CrankExpire>) -> Result<()> {
+        ctx.accounts.document.require_mutable()?;
+        
+        let document = &mut ctx.accounts.document;
+        let now = Clock::get()?.unix_timestamp;
+        
+        let signing_deadline = document
+            .signing_deadline
+            .ok_or(DocumentError::NoSigningDeadline)?;
+        require!(
+            document.signing_period_ended(now),
+            DocumentError::SigningPeriodOpen
+        );
+        document.require_active()?;
+        
+        document.status = DocumentStatus::Expired;
+        
+        emit!(SigningPeriodExpired {
+            document_id: document.key(),
+            signing_deadline,
+            signatures_count: document.signatures_count,
+            timestamp: now,
+        });
+        
+        Ok(())
+    }
+    
+    /// Grows a document account created with an older, smaller layout to
+    /// `Document::LEN` and stamps it with `CURRENT_SCHEMA_VERSION`. New
+    /// trailing fields read back as their zero value.
+    ///
+    /// Every layout change so far only appended fields, so anyone may
+    /// migrate a document and pay for the extra rent. A future migration
+    /// that rewrites existing fields should require the authority.
+    pub fn migrate_document(ctx: Context<MigrateDocument>) -> Result<()> {
+        let document = &ctx.accounts.document;
+        let payer = &ctx.accounts.payer;
+        
+        {
+            let data = document.try_borrow_data()?;
+            require!(
+                data.len() >= 8 + 32 && data[..8] == Document::DISCRIMINATOR,
+                ErrorCode::AccountDiscriminatorMismatch
+            );
+        }
+        
+        if document.data_len() < Document::LEN {
+            let required = Rent::get()?.minimum_balance(Document::LEN);
+            let top_up = required.saturating_sub(document.lamports());
+            if top_up > 0 {
+                system_program::transfer(
+                    CpiContext::new(
+                        ctx.accounts.system_program.to_account_info(),
+                        system_program::Transfer {
+                            from: payer.to_account_info(),
+                            to: document.to_account_info(),
+                        },
+                    ),
+                    top_up,
+                )?;
+            }
+            
+            document.realloc(Document::LEN, true)?;
+        }
+        
+        let document_info = document.to_account_info();
+        let mut migrated = Account::<Document>::try_from(&document_info)?;
+        let from_version = migrated.schema_version;
+        if from_version >= CURRENT_SCHEMA_VERSION {
+            return Ok(());
+        }
+        
+        migrated.schema_version = CURRENT_SCHEMA_VERSION;
+        migrated.exit(&ID)?;
+        
+        emit!(DocumentMigrated {
+            document_id: document.key(),
+            from_version,
+            to_version: CURRENT_SCHEMA_VERSION,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+    
+    /// Records the address seeds of a document created before they were
+    /// stored. Anyone may call it, since the seeds are checked against the
+    /// document's address.
+    pub fn backfill_document_bump(
+        ctx: Context<BackfillDocumentBump>,
+        registrant: Pubkey,
+        index: u64,
+    ) -> Result<()> {
+        let document = &mut ctx.accounts.document;
+        
+        require!(document.bump == 0, DocumentError::BumpAlreadySet);
+        let (address, bump) = Pubkey::find_program_address(
+            &[b"document", registrant.as_ref(), &index.to_le_bytes()],
+            &ID,
+        );
+        require_keys_eq!(address, document.key(), ErrorCode::ConstraintSeeds);
+        
+        document.registrant = registrant;
+        document.index = index;
+        document.bump = bump;
+        
+        Ok(())
+    }
+    
+    /// Records the bump of a signature created before it was stored. Anyone
+    /// may call it, since the seeds are checked against the signature's
+    /// address.
+    pub fn backfill_signature_bump(ctx: Context<BackfillSignatureBump>) -> Result<()> {
+        let signature = &mut ctx.accounts.signature;
+        
+        require!(signature.bump == 0, DocumentError::BumpAlreadySet);
+        let (address, bump) = Pubkey::find_program_address(
+            &[b"signature", signature.document.as_ref(), signature.signer.as_ref()],
+            &ID,
+        );
+        require_keys_eq!(address, signature.key(), ErrorCode::ConstraintSeeds);
+        
+        signature.bump = bump;
+        
+        Ok(())
+    }
+    
+    /// Grows the `DocumentManager` created with an older, smaller layout to
+    /// `DocumentManager::LEN` and records its bump and treasury. Other new
+    /// trailing fields read back as zero, so the statistics only cover
+    /// activity after the migration.
+    pub fn migrate_manager(ctx: Context<MigrateManager>) -> Result<()> {
+        let document_manager = &ctx.accounts.document_manager;
+        let authority = &ctx.accounts.authority;
+        
+        {
+            let data = document_manager.try_borrow_data()?;
+            require!(
+                data.len() >= 8 + 32 && data[..8] == DocumentManager::DISCRIMINATOR,
+                ErrorCode::AccountDiscriminatorMismatch
+            );
+            // The authority is the first field after the discriminator
+            require!(
+                data[8..40] == authority.key().to_bytes(),
+                DocumentError::Unauthorized
+            );
+        }
+        
+        if document_manager.data_len() < DocumentManager::LEN {
+            let required = Rent::get()?.minimum_balance(DocumentManager::LEN);
+            let top_up = required.saturating_sub(document_manager.lamports());
+            if top_up > 0 {
+                system_program::transfer(
+                    CpiContext::new(
+                        ctx.accounts.system_program.to_account_info(),
+                        system_program::Transfer {
+                            from: authority.to_account_info(),
+                            to: document_manager.to_account_info(),
+                        },
+                    ),
+                    top_up,
+                )?;
+            }
+            
+            document_manager.realloc(DocumentManager::LEN, true)?;
+        }
+        
+        let document_manager_info = document_manager.to_account_info();
+        let mut migrated = Account::<DocumentManager>::try_from(&document_manager_info)?;
+        if migrated.bump == 0
+            || migrated.schema_version < CURRENT_SCHEMA_VERSION
+            || migrated.treasury == Pubkey::default()
+        {
+            migrated.bump = ctx.bumps["document_manager"];
+            migrated.schema_version = CURRENT_SCHEMA_VERSION;
+            migrated.treasury = treasury_address();
+            migrated.exit(&ID)?;
+        }
+        
+        Ok(())
+    }
+    
+    /// Grows the caller's `AuthorityStats` from the layout without
+    /// `documents_owned`. Documents registered before the migration are not
+    /// counted against the quota.
+    pub fn migrate_authority_stats(ctx: Context<MigrateAuthorityStats>) -> Result<()> {
+        let authority_stats = &ctx.accounts.authority_stats;
+        
+        if authority_stats.data_len() >= AuthorityStats::LEN {
+            return Ok(());
+        }
+        
+        let required = Rent::get()?.minimum_balance(AuthorityStats::LEN);
+        let top_up = required.saturating_sub(authority_stats.lamports());
+        if top_up > 0 {
+            system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: ctx.accounts.authority.to_account_info(),
+                        to: authority_stats.to_account_info(),
+                    },
+                ),
+                top_up,
+            )?;
+        }
+        
+        authority_stats.realloc(AuthorityStats::LEN, true)?;
+        
+        Ok(())
+    }
+    
+    /// Fills in `document_hash_bytes` for a document registered before the
+    /// field existed. Only 32-byte digests have a byte form.
+    pub fn migrate_hash_to_bytes(ctx: Context<MigrateHashToBytes>) -> Result<()> {
+        ctx.accounts.document.require_mutable()?;
+        
+        let document = &mut ctx.accounts.document;
+        
+        require!(
+            document.authority == ctx.accounts.authority.key(),
+            DocumentError::Unauthorized
+        );
+        
+        document.document_hash_bytes = Some(
+            decode_digest_32(&document.document_hash).ok_or(DocumentError::HashNotConvertible)?,
+        );
+        
+        Ok(())
+    }
+    
+    pub fn close_document(ctx: Context<CloseDocument>, force: bool) -> Result<()> {
+        ctx.accounts.document.require_mutable()?;
+        
+        let document = &ctx.accounts.document;
+        
+        require!(
+            document.authority == ctx.accounts.authority.key(),
+            DocumentError::Unauthorized
+        );
+        require!(
+            force || document.status != DocumentStatus::Active,
+            DocumentError::DocumentActive
+        );
+        require!(!document.disputed, DocumentError::DocumentDisputed);
+        
+        // Free the slot in the registrant's quota
+        let authority_stats = &mut ctx.accounts.authority_stats;
+        authority_stats.documents_owned = authority_stats.documents_owned.saturating_sub(1);
+        
+        require!(
+            ctx.accounts.collection.as_ref().map(|collection| collection.key()) == document.collection,
+            DocumentError::DocumentNotInCollection
+        );
+        if let Some(collection) = &mut ctx.accounts.collection {
+            collection.documents_in_collection =
+                collection.documents_in_collection.saturating_sub(1);
+        }
+        
+        // Release the hash so it can be registered again. Documents from
+        // before the hash index have none to close.
+        let hash_index_info = ctx.accounts.hash_index.to_account_info();
+        if hash_index_info.owner == &ID {
+            let hash_index = Account::<HashIndex>::try_from(&hash_index_info)?;
+            if hash_index.document == document.key() {
+                hash_index.close(ctx.accounts.authority.to_account_info())?;
+            }
+        }
+        
+        emit!(DocumentClosed {
+            document_id: document.key(),
+            authority: document.authority,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+    
+    pub fn revoke_document(ctx: Context<RevokeDocument>, reason: String) -> Result<()> {
+        ctx.accounts.document.require_mutable()?;
+        
+        let document = &mut ctx.accounts.document;
+        
+        require!(
+            reason.len() <= MAX_REVOCATION_REASON_LEN,
+            DocumentError::ReasonTooLong
+        );
+        require!(
+            document.authority == ctx.accounts.authority.key(),
+            DocumentError::Unauthorized
+        );
+        require!(
+            document.status != DocumentStatus::Revoked,
+            DocumentError::DocumentRevoked
+        );
+        
+        document.status = DocumentStatus::Revoked;
+        document.revocation_reason = Some(reason.clone());
+        
+        emit!(DocumentRevoked {
+            document_id: document.key(),
+            authority: document.authority,
+            reason,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct Initialize<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = DocumentManager::LEN,
+        seeds = [b"document-manager"],
+        bump
+    )]
+    pub document_manager: Account<'info, DocumentManager>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct TransferManagerAuthority<'info> {
+    #[account(
+        mut,
+        seeds = [b"document-manager"],
+        bump = document_manager.bump
+    )]
+    pub document_manager: Account<'info, DocumentManager>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetPaused<'info> {
+    #[account(
+        mut,
+        seeds = [b"document-manager"],
+        bump = document_manager.bump
+    )]
+    pub document_manager: Account<'info, DocumentManager>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetRegistrationMode<'info> {
+    #[account(
+        mut,
+        seeds = [b"document-manager"],
+        bump = document_manager.bump
+    )]
+    pub document_manager: Account<'info, DocumentManager>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(name: String)]
+pub struct CreateCategory<'info> {
+    #[account(
+        seeds = [b"document-manager"],
+        bump = document_manager.bump
+    )]
+    pub document_manager: Account<'info, DocumentManager>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = Category::LEN,
+        seeds = [b"category", name.as_bytes()],
+        bump
+    )]
+    pub category: Account<'info, Category>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(name: String)]
+pub struct CreateCollection<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = Collection::LEN,
+        seeds = [b"collection", authority.key().as_ref(), name.as_bytes()],
+        bump
+    )]
+    pub collection: Account<'info, Collection>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ArchiveCollection<'info> {
+    #[account(
+        mut,
+        seeds = [b"document-manager"],
+        bump = document_manager.bump
+    )]
+    pub document_manager: Account<'info, DocumentManager>,
+    
+    pub collection: Account<'info, Collection>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct MoveDocument<'info> {
+    #[account(
+        mut,
+        seeds = [b"document", document.registrant.as_ref(), &document.index.to_le_bytes()],
+        bump = document.bump
+    )]
+    pub document: Account<'info, Document>,
+    
+    #[account(mut)]
+    pub from_collection: Account<'info, Collection>,
+    
+    #[account(mut)]
+    pub to_collection: Account<'info, Collection>,
+    
+    pub from_authority: Signer<'info>,
+    pub to_authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(name: String)]
+pub struct CreateOrganization<'info> {
+    #[account(
+        init,
+        payer = admin,
+        space = Organization::LEN,
+        seeds = [b"organization", name.as_bytes()],
+        bump
+    )]
+    pub organization: Account<'info, Organization>,
+    
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(member: Pubkey)]
+pub struct AddMember<'info> {
+    #[account(mut)]
+    pub organization: Account<'info, Organization>,
+    
+    #[account(
+        init,
+        payer = admin,
+        space = OrgMember::LEN,
+        seeds = [b"member", organization.key().as_ref(), member.as_ref()],
+        bump
+    )]
+    pub org_member: Account<'info, OrgMember>,
+    
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveMember<'info> {
+    #[account(mut)]
+    pub organization: Account<'info, Organization>,
+    
+    #[account(
+        mut,
+        close = admin,
+        seeds = [b"member", organization.key().as_ref(), org_member.member.as_ref()],
+        bump
+    )]
+    pub org_member: Account<'info, OrgMember>,
+    
+    #[account(mut)]
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetRegistrationFee<'info> {
+    #[account(
+        mut,
+        seeds = [b"document-manager"],
+        bump = document_manager.bump
+    )]
+    pub document_manager: Account<'info, DocumentManager>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetAuthorityQuota<'info> {
+    #[account(
+        mut,
+        seeds = [b"document-manager"],
+        bump = document_manager.bump
+    )]
+    pub document_manager: Account<'info, DocumentManager>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetTokenFee<'info> {
+    #[account(
+        mut,
+        seeds = [b"document-manager"],
+        bump = document_manager.bump
+    )]
+    pub document_manager: Account<'info, DocumentManager>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawTreasury<'info> {
+    #[account(
+        seeds = [b"document-manager"],
+        bump = document_manager.bump
+    )]
+    pub document_manager: Account<'info, DocumentManager>,
+    
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump,
+        address = document_manager.treasury @ DocumentError::InvalidTreasury
+    )]
+    pub treasury: SystemAccount<'info>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(registrar: Pubkey)]
+pub struct AddRegistrar<'info> {
+    #[account(
+        seeds = [b"document-manager"],
+        bump = document_manager.bump
+    )]
+    pub document_manager: Account<'info, DocumentManager>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = AllowlistEntry::LEN,
+        seeds = [b"allowlist", registrar.as_ref()],
+        bump
+    )]
+    pub allowlist_entry: Account<'info, AllowlistEntry>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveRegistrar<'info> {
+    #[account(
+        seeds = [b"document-manager"],
+        bump = document_manager.bump
+    )]
+    pub document_manager: Account<'info, DocumentManager>,
+    
+    #[account(
+        mut,
+        close = authority,
+        seeds = [b"allowlist", allowlist_entry.registrar.as_ref()],
+        bump
+    )]
+    pub allowlist_entry: Account<'info, AllowlistEntry>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(notary: Pubkey)]
+pub struct AddNotary<'info> {
+    #[account(
+        seeds = [b"document-manager"],
+        bump = document_manager.bump
+    )]
+    pub document_manager: Account<'info, DocumentManager>,
+    
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = NotaryRegistry::LEN,
+        seeds = [b"notary-registry"],
+        bump
+    )]
+    pub notary_registry: Account<'info, NotaryRegistry>,
+    
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = NotaryEntry::LEN,
+        seeds = [b"notary", notary.as_ref()],
+        bump
+    )]
+    pub notary_entry: Account<'info, NotaryEntry>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveNotary<'info> {
+    #[account(
+        seeds = [b"document-manager"],
+        bump = document_manager.bump
+    )]
+    pub document_manager: Account<'info, DocumentManager>,
+    
+    #[account(
+        mut,
+        seeds = [b"notary-registry"],
+        bump
+    )]
+    pub notary_registry: Account<'info, NotaryRegistry>,
+    
+    #[account(
+        mut,
+        seeds = [b"notary", notary_entry.notary.as_ref()],
+        bump
+    )]
+    pub notary_entry: Account<'info, NotaryEntry>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptManagerAuthority<'info> {
+    #[account(
+        mut,
+        seeds = [b"document-manager"],
+        bump = document_manager.bump
+    )]
+    pub document_manager: Account<'info, DocumentManager>,
+    
+    pub new_authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(document_hash: String)]
+pub struct RegisterDocument<'info> {
+    #[account(
+        mut,
+        seeds = [b"document-manager"],
+        bump = document_manager.bump
+    )]
+    pub document_manager: Account<'info, DocumentManager>,
+    
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = AuthorityStats::LEN,
+        seeds = [b"authority", authority.key().as_ref()],
+        bump
+    )]
+    pub authority_stats: Account<'info, AuthorityStats>,
+    
+    /// Derived from the registering authority's own counter,
+    /// `[b"document", authority, authority_stats.document_count (u64 LE)]`,
+    /// so registrations by different authorities never contend.
+    #[account(
+        init,
+        payer = authority,
+        space = Document::LEN,
+        seeds = [b"document", authority.key().as_ref(), &authority_stats.document_count.to_le_bytes()],
+        bump
+    )]
+    pub document: Account<'info, Document>,
+    
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = HashIndex::LEN,
+        seeds = [b"hash", hash(document_hash.as_bytes()).as_ref()],
+        bump
+    )]
+    pub hash_index: Account<'info, HashIndex>,
+    
+    /// CHECK: validated by `Category::check` so a missing or foreign account
+    /// fails with `UnknownCategory`
+    pub category: Option<UncheckedAccount<'info>>,
+    
+    /// CHECK: validated by `Document::check_parent` so a missing or foreign
+    /// account fails with `InvalidParent`
+    pub parent: Option<UncheckedAccount<'info>>,
+    
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump,
+        address = document_manager.treasury @ DocumentError::InvalidTreasury
+    )]
+    pub treasury: SystemAccount<'info>,
+    
+    /// Token fee path: the payer's account of the manager's `fee_mint`
+    #[account(mut)]
+    pub payer_token_account: Option<Account<'info, TokenAccount>>,
+    
+    /// Token fee path: a `fee_mint` account owned by the treasury PDA
+    #[account(mut)]
+    pub treasury_token_account: Option<Account<'info, TokenAccount>>,
+    
+    pub token_program: Option<Program<'info, Token>>,
+    
+    /// Required when the manager is in `RegistrationMode::Allowlisted`
+    pub allowlist_entry: Option<Account<'info, AllowlistEntry>>,
+    
+    /// Registers the document as owned by this organization instead of
+    /// `authority`, which must then hold the `Registrar` role
+    pub organization: Option<Account<'info, Organization>>,
+    
+    /// `authority`'s membership, unless it is the organization admin
+    pub org_member: Option<Account<'info, OrgMember>>,
+    
+    /// Files the document in this collection, which `authority` must own
+    #[account(mut)]
+    pub collection: Option<Account<'info, Collection>>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RegisterDocumentsBatch<'info> {
+    #[account(
+        mut,
+        seeds = [b"document-manager"],
+        bump = document_manager.bump
+    )]
+    pub document_manager: Account<'info, DocumentManager>,
+    
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = AuthorityStats::LEN,
+        seeds = [b"authority", authority.key().as_ref()],
+        bump
+    )]
+    pub authority_stats: Account<'info, AuthorityStats>,
+    
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump,
+        address = document_manager.treasury @ DocumentError::InvalidTreasury
+    )]
+    pub treasury: SystemAccount<'info>,
+    
+    /// Required when the manager is in `RegistrationMode::Allowlisted`
+    pub allowlist_entry: Option<Account<'info, AllowlistEntry>>,
+    
+    /// CHECK: validated by `Category::check`; the category entries may name
+    pub category: Option<UncheckedAccount<'info>>,
+    
+    /// CHECK: validated by `Document::check_parent`; the parent entries may
+    /// name
+    pub parent: Option<UncheckedAccount<'info>>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(merkle_root: [u8; 32])]
+pub struct RegisterMerkleBatch<'info> {
+    #[account(
+        seeds = [b"document-manager"],
+        bump = document_manager.bump
+    )]
+    pub document_manager: Account<'info, DocumentManager>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = MerkleBatch::LEN,
+        seeds = [b"merkle", authority.key().as_ref(), merkle_root.as_ref()],
+        bump
+    )]
+    pub merkle_batch: Account<'info, MerkleBatch>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct VerifyMerkleInclusion<'info> {
+    pub merkle_batch: Account<'info, MerkleBatch>,
+}
+
+#[derive(Accounts)]
+pub struct ReviewDocument<'info> {
+    #[account(
+        mut,
+        seeds = [b"document", document.registrant.as_ref(), &document.index.to_le_bytes()],
+        bump = document.bump
+    )]
+    pub document: Account<'info, Document>,
+    
+    pub reviewer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateDocument<'info> {
+    #[account(
+        mut,
+        seeds = [b"document", document.registrant.as_ref(), &document.index.to_le_bytes()],
+        bump = document.bump
+    )]
+    pub document: Account<'info, Document>,
+    
+    /// Record of the version this update creates
+    #[account(
+        init,
+        payer = authority,
+        space = DocumentVersion::LEN,
+        seeds = [b"version", document.key().as_ref(), &(document.version + 1).to_le_bytes()],
+        bump
+    )]
+    pub document_version: Account<'info, DocumentVersion>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    /// Required when `authority` acts through a delegation
+    pub delegation: Option<Account<'info, Delegation>>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SignDocument<'info> {
+    #[account(
+        mut,
+        seeds = [b"document-manager"],
+        bump = document_manager.bump
+    )]
+    pub document_manager: Account<'info, DocumentManager>,
+    
+    #[account(
+        mut,
+        seeds = [b"document", document.registrant.as_ref(), &document.index.to_le_bytes()],
+        bump = document.bump
+    )]
+    pub document: Account<'info, Document>,
+    
+    #[account(
+        init_if_needed,
+        payer = signer,
+        space = DocumentSignature::LEN,
+        seeds = [b"signature", document.key().as_ref(), principal.key().as_ref()],
+        bump
+    )]
+    pub signature: Account<'info, DocumentSignature>,
+    
+    /// CHECK: the party the signature is recorded for; either `signer` itself
+    /// or an authority that delegated signing to `signer`
+    pub principal: UncheckedAccount<'info>,
+    
+    /// Required when `signer` signs on behalf of `principal`
+    pub delegation: Option<Account<'info, Delegation>>,
+    
+    /// The request being answered, if the signature was requested
+    #[account(
+        mut,
+        seeds = [b"signature-request", document.key().as_ref(), principal.key().as_ref()],
+        bump
+    )]
+    pub signature_request: Option<Account<'info, SignatureRequest>>,
+    
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SignDocumentMulti<'info> {
+    #[account(
+        mut,
+        seeds = [b"document-manager"],
+        bump = document_manager.bump
+    )]
+    pub document_manager: Account<'info, DocumentManager>,
+    
+    #[account(
+        mut,
+        seeds = [b"document", document.registrant.as_ref(), &document.index.to_le_bytes()],
+        bump = document.bump
+    )]
+    pub document: Account<'info, Document>,
+    
+    /// Pays for every signature account
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitSignatureTree<'info> {
+    #[account(
+        mut,
+        seeds = [b"document", document.registrant.as_ref(), &document.index.to_le_bytes()],
+        bump = document.bump
+    )]
+    pub document: Account<'info, Document>,
+    
+    /// CHECK: allocated by the client and validated by the compression program
+    #[account(mut)]
+    pub merkle_tree: UncheckedAccount<'info>,
+    
+    /// CHECK: signs for the tree on the document's behalf; holds no data
+    #[account(
+        seeds = [b"tree-authority", document.key().as_ref()],
+        bump
+    )]
+    pub tree_authority: UncheckedAccount<'info>,
+    
+    pub authority: Signer<'info>,
+    pub compression_program: Program<'info, SplAccountCompression>,
+    pub noop_program: Program<'info, Noop>,
+}
+
+#[derive(Accounts)]
+pub struct AppendSignatureLeaf<'info> {
+    #[account(
+        seeds = [b"document-manager"],
+        bump = document_manager.bump
+    )]
+    pub document_manager: Account<'info, DocumentManager>,
+    
+    #[account(
+        mut,
+        seeds = [b"document", document.registrant.as_ref(), &document.index.to_le_bytes()],
+        bump = document.bump
+    )]
+    pub document: Account<'info, Document>,
+    
+    /// CHECK: must be the document's `signature_tree`; validated in the handler
+    #[account(mut)]
+    pub merkle_tree: UncheckedAccount<'info>,
+    
+    /// CHECK: signs for the tree on the document's behalf; holds no data
+    #[account(
+        seeds = [b"tree-authority", document.key().as_ref()],
+        bump
+    )]
+    pub tree_authority: UncheckedAccount<'info>,
+    
+    pub signer: Signer<'info>,
+    pub compression_program: Program<'info, SplAccountCompression>,
+    pub noop_program: Program<'info, Noop>,
+}
+
+#[derive(Accounts)]
+pub struct VerifySignatureLeaf<'info> {
+    #[account(
+        seeds = [b"document", document.registrant.as_ref(), &document.index.to_le_bytes()],
+        bump = document.bump
+    )]
+    pub document: Account<'info, Document>,
+    
+    /// CHECK: must be the document's `signature_tree`; validated in the handler
+    pub merkle_tree: UncheckedAccount<'info>,
+    
+    pub compression_program: Program<'info, SplAccountCompression>,
+}
+
+#[derive(Accounts)]
+pub struct SignDocumentVerified<'info> {
+    pub sign: SignDocument<'info>,
+    
+    /// CHECK: the instructions sysvar, pinned by address
+    #[account(address = sysvar::instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct VerifySignatureCurrent<'info> {
+    #[account(
+        seeds = [b"document", document.registrant.as_ref(), &document.index.to_le_bytes()],
+        bump = document.bump
+    )]
+    pub document: Account<'info, Document>,
+    
+    #[account(
+        seeds = [b"signature", document.key().as_ref(), signature.signer.as_ref()],
+        bump = signature.bump
+    )]
+    pub signature: Account<'info, DocumentSignature>,
+}
+
+#[derive(Accounts)]
+pub struct GetDocument<'info> {
+    #[account(
+        seeds = [b"document", document.registrant.as_ref(), &document.index.to_le_bytes()],
+        bump = document.bump
+    )]
+    pub document: Account<'info, Document>,
+}
+
+#[derive(Accounts)]
+pub struct GetManagerStats<'info> {
+    #[account(
+        seeds = [b"document-manager"],
+        bump = document_manager.bump
+    )]
+    pub document_manager: Account<'info, DocumentManager>,
+}
+
+#[derive(Accounts)]
+pub struct VerifyDocument<'info> {
+    #[account(
+        seeds = [b"document", document.registrant.as_ref(), &document.index.to_le_bytes()],
+        bump = document.bump
+    )]
+    pub document: Account<'info, Document>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeSignature<'info> {
+    #[account(
+        mut,
+        seeds = [b"document", document.registrant.as_ref(), &document.index.to_le_bytes()],
+        bump = document.bump
+    )]
+    pub document: Account<'info, Document>,
+    
+    #[account(
+        mut,
+        close = signer,
+        seeds = [b"signature", document.key().as_ref(), signer.key().as_ref()],
+        bump = signature.bump
+    )]
+    pub signature: Account<'info, DocumentSignature>,
+    
+    #[account(mut)]
+    pub signer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct PruneExpiredSignatures<'info> {
+    #[account(
+        mut,
+        seeds = [b"document", document.registrant.as_ref(), &document.index.to_le_bytes()],
+        bump = document.bump
+    )]
+    pub document: Account<'info, Document>,
+}
+
+#[derive(Accounts)]
+#[instruction(signer: Pubkey)]
+pub struct RequestSignature<'info> {
+    #[account(
+        seeds = [b"document", document.registrant.as_ref(), &document.index.to_le_bytes()],
+        bump = document.bump
+    )]
+    pub document: Account<'info, Document>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = SignatureRequest::LEN,
+        seeds = [b"signature-request", document.key().as_ref(), signer.as_ref()],
+        bump
+    )]
+    pub signature_request: Account<'info, SignatureRequest>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct DeclineSignature<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"signature-request",
+            signature_request.document.as_ref(),
+            signer.key().as_ref()
+        ],
+        bump
+    )]
+    pub signature_request: Account<'info, SignatureRequest>,
+    
+    pub signer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CloseSignatureRequest<'info> {
+    #[account(
+        mut,
+        close = authority,
+        seeds = [
+            b"signature-request",
+            signature_request.document.as_ref(),
+            signature_request.signer.as_ref()
+        ],
+        bump
+    )]
+    pub signature_request: Account<'info, SignatureRequest>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct NotarizeDocument<'info> {
+    #[account(
+        mut,
+        seeds = [b"document", document.registrant.as_ref(), &document.index.to_le_bytes()],
+        bump = document.bump
+    )]
+    pub document: Account<'info, Document>,
+    
+    #[account(
+        seeds = [b"notary", notary.key().as_ref()],
+        bump
+    )]
+    pub notary_entry: Account<'info, NotaryEntry>,
+    
+    #[account(
+        init,
+        payer = notary,
+        space = Notarization::LEN,
+        seeds = [b"notarization", document.key().as_ref(), notary.key().as_ref()],
+        bump
+    )]
+    pub notarization: Account<'info, Notarization>,
+    
+    #[account(mut)]
+    pub notary: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct HasNotarization<'info> {
+    #[account(
+        seeds = [b"document", document.registrant.as_ref(), &document.index.to_le_bytes()],
+        bump = document.bump
+    )]
+    pub document: Account<'info, Document>,
+    
+    /// CHECK: only used as a seed
+    pub notary: UncheckedAccount<'info>,
+    
+    /// CHECK: may not exist; ownership and data are inspected by the handler
+    #[account(
+        seeds = [b"notarization", document.key().as_ref(), notary.key().as_ref()],
+        bump
+    )]
+    pub notarization: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(attestation_type: u8)]
+pub struct AttestDocument<'info> {
+    #[account(
+        seeds = [b"document", document.registrant.as_ref(), &document.index.to_le_bytes()],
+        bump = document.bump
+    )]
+    pub document: Account<'info, Document>,
+    
+    #[account(
+        init,
+        payer = attester,
+        space = Attestation::LEN,
+        seeds = [
+            b"attestation",
+            document.key().as_ref(),
+            attester.key().as_ref(),
+            &[attestation_type],
+        ],
+        bump
+    )]
+    pub attestation: Account<'info, Attestation>,
+    
+    #[account(mut)]
+    pub attester: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeAttestation<'info> {
+    #[account(
+        mut,
+        close = attester,
+        seeds = [
+            b"attestation",
+            attestation.document.as_ref(),
+            attester.key().as_ref(),
+            &[attestation.attestation_type],
+        ],
+        bump
+    )]
+    pub attestation: Account<'info, Attestation>,
+    
+    #[account(mut)]
+    pub attester: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RaiseDispute<'info> {
+    #[account(
+        mut,
+        seeds = [b"document", document.registrant.as_ref(), &document.index.to_le_bytes()],
+        bump = document.bump
+    )]
+    pub document: Account<'info, Document>,
+    
+    /// Proves `signer` is a party to the document
+    #[account(
+        seeds = [b"signature", document.key().as_ref(), signer.key().as_ref()],
+        bump = signature.bump
+    )]
+    pub signature: Account<'info, DocumentSignature>,
+    
+    #[account(
+        init,
+        payer = signer,
+        space = Dispute::LEN,
+        seeds = [b"dispute", document.key().as_ref()],
+        bump
+    )]
+    pub dispute: Account<'info, Dispute>,
+    
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ResolveDispute<'info> {
+    #[account(
+        seeds = [b"document-manager"],
+        bump = document_manager.bump
+    )]
+    pub document_manager: Account<'info, DocumentManager>,
+    
+    #[account(
+        mut,
+        seeds = [b"document", document.registrant.as_ref(), &document.index.to_le_bytes()],
+        bump = document.bump
+    )]
+    pub document: Account<'info, Document>,
+    
+    #[account(
+        mut,
+        close = raised_by,
+        seeds = [b"dispute", document.key().as_ref()],
+        bump
+    )]
+    pub dispute: Account<'info, Dispute>,
+    
+    /// CHECK: receives the dispute's rent back
+    #[account(mut, address = dispute.raised_by)]
+    pub raised_by: UncheckedAccount<'info>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct LockDocument<'info> {
+    #[account(
+        mut,
+        seeds = [b"document", document.registrant.as_ref(), &document.index.to_le_bytes()],
+        bump = document.bump
+    )]
+    pub document: Account<'info, Document>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetLegalHold<'info> {
+    #[account(
+        seeds = [b"document-manager"],
+        bump = document_manager.bump
+    )]
+    pub document_manager: Account<'info, DocumentManager>,
+    
+    #[account(
+        mut,
+        seeds = [b"document", document.registrant.as_ref(), &document.index.to_le_bytes()],
+        bump = document.bump
+    )]
+    pub document: Account<'info, Document>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(relation: DocumentRelation)]
+pub struct LinkDocuments<'info> {
+    #[account(
+        seeds = [b"document", source.registrant.as_ref(), &source.index.to_le_bytes()],
+        bump = source.bump
+    )]
+    pub source: Account<'info, Document>,
+    #[account(
+        seeds = [b"document", target.registrant.as_ref(), &target.index.to_le_bytes()],
+        bump = target.bump
+    )]
+    pub target: Account<'info, Document>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = DocumentLink::LEN,
+        seeds = [b"link", source.key().as_ref(), target.key().as_ref(), &[relation as u8]],
+        bump
+    )]
+    pub link: Account<'info, DocumentLink>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UnlinkDocuments<'info> {
+    #[account(
+        address = link.source,
+        seeds = [b"document", source.registrant.as_ref(), &source.index.to_le_bytes()],
+        bump = source.bump
+    )]
+    pub source: Account<'info, Document>,
+    
+    #[account(
+        mut,
+        close = authority,
+        seeds = [b"link", link.source.as_ref(), link.target.as_ref(), &[link.relation as u8]],
+        bump
+    )]
+    pub link: Account<'info, DocumentLink>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ArchiveDocument<'info> {
+    #[account(
+        mut,
+        seeds = [b"document-manager"],
+        bump = document_manager.bump
+    )]
+    pub document_manager: Account<'info, DocumentManager>,
+    
+    #[account(
+        mut,
+        seeds = [b"document", document.registrant.as_ref(), &document.index.to_le_bytes()],
+        bump = document.bump
+    )]
+    pub document: Account<'info, Document>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    /// Required when `authority` acts through a delegation
+    pub delegation: Option<Account<'info, Delegation>>,
+    
+    /// Owner of an organization-owned document
+    pub organization: Option<Account<'info, Organization>>,
+    
+    /// `authority`'s membership, unless it is the organization admin
+    pub org_member: Option<Account<'info, OrgMember>>,
+}
+
+#[derive(Accounts)]
+#[instruction(delegate: Pubkey)]
+pub struct CreateDelegation<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = Delegation::LEN,
+        seeds = [b"delegation", authority.key().as_ref(), delegate.as_ref()],
+        bump
+    )]
+    pub delegation: Account<'info, Delegation>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeDelegation<'info> {
+    #[account(
+        mut,
+        close = authority,
+        seeds = [b"delegation", authority.key().as_ref(), delegation.delegate.as_ref()],
+        bump
+    )]
+    pub delegation: Account<'info, Delegation>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UnarchiveDocument<'info> {
+    #[account(
+        mut,
+        seeds = [b"document", document.registrant.as_ref(), &document.index.to_le_bytes()],
+        bump = document.bump
+    )]
+    pub document: Account<'info, Document>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct TransferOwnership<'info> {
+    #[account(
+        mut,
+        seeds = [b"document", document.registrant.as_ref(), &document.index.to_le_bytes()],
+        bump = document.bump
+    )]
+    pub document: Account<'info, Document>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    /// CHECK: only its key is recorded as the new document authority
+    pub new_authority: UncheckedAccount<'info>,
+    
+    /// Owner of an organization-owned document
+    pub organization: Option<Account<'info, Organization>>,
+    
+    /// `authority`'s membership, unless it is the organization admin
+    pub org_member: Option<Account<'info, OrgMember>>,
+}
+
+#[derive(Accounts)]
+pub struct ProposeTransfer<'info> {
+    #[account(
+        mut,
+        seeds = [b"document", document.registrant.as_ref(), &document.index.to_le_bytes()],
+        bump = document.bump
+    )]
+    pub document: Account<'info, Document>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    /// Owner of an organization-owned document
+    pub organization: Option<Account<'info, Organization>>,
+    
+    /// `authority`'s membership, unless it is the organization admin
+    pub org_member: Option<Account<'info, OrgMember>>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptTransfer<'info> {
+    #[account(
+        mut,
+        seeds = [b"document", document.registrant.as_ref(), &document.index.to_le_bytes()],
+        bump = document.bump
+    )]
+    pub document: Account<'info, Document>,
+    
+    #[account(mut)]
+    pub new_owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CancelTransfer<'info> {
+    #[account(
+        mut,
+        seeds = [b"document", document.registrant.as_ref(), &document.index.to_le_bytes()],
+        bump = document.bump
+    )]
+    pub document: Account<'info, Document>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetDelegate<'info> {
+    #[account(
+        mut,
+        seeds = [b"document", document.registrant.as_ref(), &document.index.to_le_bytes()],
+        bump = document.bump
+    )]
+    pub document: Account<'info, Document>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetOperator<'info> {
+    #[account(
+        mut,
+        seeds = [b"document", document.registrant.as_ref(), &document.index.to_le_bytes()],
+        bump = document.bump
+    )]
+    pub document: Account<'info, Document>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetSignatureThreshold<'info> {
+    #[account(
+        mut,
+        seeds = [b"document", document.registrant.as_ref(), &document.index.to_le_bytes()],
+        bump = document.bump
+    )]
+    pub document: Account<'info, Document>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateStructuredMetadata<'info> {
+    #[account(
+        mut,
+        seeds = [b"document", document.registrant.as_ref(), &document.index.to_le_bytes()],
+        bump = document.bump
+    )]
+    pub document: Account<'info, Document>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    /// Required when `authority` acts through a delegation
+    pub delegation: Option<Account<'info, Delegation>>,
+}
+
+#[derive(Accounts)]
+pub struct RenameDocument<'info> {
+    #[account(
+        mut,
+        seeds = [b"document", document.registrant.as_ref(), &document.index.to_le_bytes()],
+        bump = document.bump
+    )]
+    pub document: Account<'info, Document>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RecategorizeDocument<'info> {
+    #[account(
+        mut,
+        seeds = [b"document", document.registrant.as_ref(), &document.index.to_le_bytes()],
+        bump = document.bump
+    )]
+    pub document: Account<'info, Document>,
+    
+    /// CHECK: validated by `Category::check`; omit to clear the category
+    pub category: Option<UncheckedAccount<'info>>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(grantee: Pubkey)]
+pub struct GrantAccess<'info> {
+    #[account(
+        seeds = [b"document", document.registrant.as_ref(), &document.index.to_le_bytes()],
+        bump = document.bump
+    )]
+    pub document: Account<'info, Document>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = AccessGrant::LEN,
+        seeds = [b"grant", document.key().as_ref(), grantee.as_ref()],
+        bump
+    )]
+    pub grant: Account<'info, AccessGrant>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeAccess<'info> {
+    #[account(
+        seeds = [b"document", document.registrant.as_ref(), &document.index.to_le_bytes()],
+        bump = document.bump
+    )]
+    pub document: Account<'info, Document>,
+    
+    #[account(
+        mut,
+        close = authority,
+        seeds = [b"grant", document.key().as_ref(), grant.grantee.as_ref()],
+        bump
+    )]
+    pub grant: Account<'info, AccessGrant>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetEncryptedKey<'info> {
+    #[account(
+        mut,
+        seeds = [b"document", document.registrant.as_ref(), &document.index.to_le_bytes()],
+        bump = document.bump
+    )]
+    pub document: Account<'info, Document>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetWrappedKey<'info> {
+    #[account(
+        seeds = [b"document", document.registrant.as_ref(), &document.index.to_le_bytes()],
+        bump = document.bump
+    )]
+    pub document: Account<'info, Document>,
+    
+    /// Grown to the current layout if it predates `wrapped_key`
+    #[account(
+        mut,
+        realloc = AccessGrant::LEN,
+        realloc::payer = authority,
+        realloc::zero = true,
+        seeds = [b"grant", document.key().as_ref(), grant.grantee.as_ref()],
+        bump
+    )]
+    pub grant: Account<'info, AccessGrant>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(key: String)]
+pub struct SetAttribute<'info> {
+    #[account(
+        seeds = [b"document", document.registrant.as_ref(), &document.index.to_le_bytes()],
+        bump = document.bump
+    )]
+    pub document: Account<'info, Document>,
+    
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = DocumentAttribute::LEN,
+        seeds = [b"attr", document.key().as_ref(), key.as_bytes()],
+        bump
+    )]
+    pub attribute: Account<'info, DocumentAttribute>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveAttribute<'info> {
+    #[account(
+        seeds = [b"document", document.registrant.as_ref(), &document.index.to_le_bytes()],
+        bump = document.bump
+    )]
+    pub document: Account<'info, Document>,
+    
+    #[account(
+        mut,
+        close = authority,
+        seeds = [b"attr", document.key().as_ref(), attribute.key.as_bytes()],
+        bump
+    )]
+    pub attribute: Account<'info, DocumentAttribute>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(tag: String)]
+pub struct AddTag<'info> {
+    #[account(
+        mut,
+        seeds = [b"document", document.registrant.as_ref(), &document.index.to_le_bytes()],
+        bump = document.bump
+    )]
+    pub document: Account<'info, Document>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = DocumentTag::LEN,
+        seeds = [b"tag", hash(tag.as_bytes()).as_ref(), document.key().as_ref()],
+        bump
+    )]
+    pub document_tag: Account<'info, DocumentTag>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveTag<'info> {
+    #[account(
+        mut,
+        seeds = [b"document", document.registrant.as_ref(), &document.index.to_le_bytes()],
+        bump = document.bump
+    )]
+    pub document: Account<'info, Document>,
+    
+    #[account(
+        mut,
+        close = authority,
+        seeds = [b"tag", document_tag.tag_hash.as_ref(), document.key().as_ref()],
+        bump
+    )]
+    pub document_tag: Account<'info, DocumentTag>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetStorageUri<'info> {
+    #[account(
+        mut,
+        seeds = [b"document", document.registrant.as_ref(), &document.index.to_le_bytes()],
+        bump = document.bump
+    )]
+    pub document: Account<'info, Document>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetExpiry<'info> {
+    #[account(
+        mut,
+        seeds = [b"document", document.registrant.as_ref(), &document.index.to_le_bytes()],
+        bump = document.bump
+    )]
+    pub document: Account<'info, Document>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ExtendExpiry<'info> {
+    #[account(
+        mut,
+        seeds = [b"document", document.registrant.as_ref(), &document.index.to_le_bytes()],
+        bump = document.bump
+    )]
+    pub document: Account<'info, Document>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CrankExpire<'info> {
+    #[account(
+        mut,
+        seeds = [b"document", document.registrant.as_ref(), &document.index.to_le_bytes()],
+        bump = document.bump
+    )]
+    pub document: Account<'info, Document>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateDocument<'info> {
+    /// CHECK: may still use an older `Document` layout; validated in the handler
+    #[account(mut, owner = ID)]
+    pub document: UncheckedAccount<'info>,
+    
+    /// Pays any extra rent; need not be the document's authority
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct BackfillDocumentBump<'info> {
+    #[account(mut)]
+    pub document: Account<'info, Document>,
+}
+
+#[derive(Accounts)]
+pub struct BackfillSignatureBump<'info> {
+    #[account(mut)]
+    pub signature: Account<'info, DocumentSignature>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateManager<'info> {
+    /// CHECK: may still use an older `DocumentManager` layout; validated in the handler
+    #[account(
+        mut,
+        owner = ID,
+        seeds = [b"document-manager"],
+        bump
+    )]
+    pub document_manager: UncheckedAccount<'info>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateAuthorityStats<'info> {
+    /// CHECK: may still use the older `AuthorityStats` layout; pinned by seeds
+    #[account(
+        mut,
+        owner = ID,
+        seeds = [b"authority", authority.key().as_ref()],
+        bump
+    )]
+    pub authority_stats: UncheckedAccount<'info>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateHashToBytes<'info> {
+    #[account(
+        mut,
+        seeds = [b"document", document.registrant.as_ref(), &document.index.to_le_bytes()],
+        bump = document.bump
+    )]
+    pub document: Account<'info, Document>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CloseDocument<'info> {
+    #[account(
+        mut,
+        close = authority,
+        seeds = [b"document", document.registrant.as_ref(), &document.index.to_le_bytes()],
+        bump = document.bump
+    )]
+    pub document: Account<'info, Document>,
+    
+    /// The registrant's stats, which release the document from their quota.
+    /// Stats in the older layout must go through `migrate_authority_stats`
+    /// first.
+    #[account(
+        mut,
+        seeds = [b"authority", document.registrant.as_ref()],
+        bump
+    )]
+    pub authority_stats: Account<'info, AuthorityStats>,
+    
+    /// The document's collection; required when the document is in one
+    #[account(mut)]
+    pub collection: Option<Account<'info, Collection>>,
+    
+    /// CHECK: the document hash's `HashIndex`, pinned by seeds; closed in the
+    /// handler when it points at this document
+    #[account(
+        mut,
+        seeds = [b"hash", hash(document.document_hash.as_bytes()).as_ref()],
+        bump
+    )]
+    pub hash_index: UncheckedAccount<'info>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeDocument<'info> {
+    #[account(
+        mut,
+        seeds = [b"document", document.registrant.as_ref(), &document.index.to_le_bytes()],
+        bump = document.bump
+    )]
+    pub document: Account<'info, Document>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+#[account]
+pub struct DocumentManager {
+    pub authority: Pubkey,
+    pub document_count: u64,
+    pub pending_authority: Option<Pubkey>,
+    pub paused: bool,
+    pub registration_mode: RegistrationMode,
+    pub registration_fee_lamports: u64,
+    pub fee_mint: Option<Pubkey>,
+    pub token_fee_amount: u64,
+    /// Open documents an authority may have registered; 0 means unlimited
+    pub max_documents_per_authority: u64,
+    /// Documents ever archived; unarchiving does not decrement it
+    pub archived_count: u64,
+    /// Signatures recorded across all documents, of every kind
+    pub total_signatures: u64,
+    /// Time of the last registration, signature or archive
+    pub last_activity_ts: i64,
+    pub bump: u8,
+    pub schema_version: u8,
+    /// Account registration fees are paid into
+    pub treasury: Pubkey,
+}
+
+impl DocumentManager {
+    pub const LEN: usize = 8 + 32 + 8 + (1 + 32) + 1 + 1 + 8 + (1 + 32) + 8 + 8 + 8 + 8 + 8 + 1 + 1 + 32;
+    
+    /// Fails if registering `count` more documents would take an authority
+    /// with `documents_owned` open documents past the quota.
+    pub fn check_quota(&self, documents_owned: u64, count: u64) -> Result<()> {
+        require!(
+            self.max_documents_per_authority == 0
+                || documents_owned.saturating_add(count) <= self.max_documents_per_authority,
+            DocumentError::AuthorityQuotaExceeded
+        );
+        Ok(())
+    }
+    
+    /// Fails unless `registrar` may register documents under the current
+    /// registration mode.
+    pub fn check_registrar(
+        &self,
+        registrar: &Pubkey,
+        allowlist_entry: Option<&Account<AllowlistEntry>>,
+    ) -> Result<()> {
+        match self.registration_mode {
+            RegistrationMode::Open => Ok(()),
+            RegistrationMode::Allowlisted => {
+                require!(
+                    allowlist_entry.is_some_and(|entry| entry.registrar == *registrar),
+                    DocumentError::RegistrarNotAllowed
+                );
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Marks `registrar` as allowed to register documents in
+/// `RegistrationMode::Allowlisted`.
+#[account]
+pub struct AllowlistEntry {
+    pub registrar: Pubkey,
+    pub added_at: i64,
+}
+
+impl AllowlistEntry {
+    pub const LEN: usize = 8 + 32 + 8;
+}
+
+/// A grouping documents can be filed under, created by the manager authority.
+#[account]
+pub struct Category {
+    pub name: String,
+    pub created_at: i64,
+}
+
+impl Category {
+    pub const LEN: usize = 8 + (4 + MAX_CATEGORY_NAME_LEN) + 8;
+    
+    /// Returns the key of `account` if it is a category created by this
+    /// program, and fails with `UnknownCategory` otherwise.
+    pub fn check(account: &AccountInfo) -> Result<Pubkey> {
+        let data = account.try_borrow_data()?;
+        require!(
+            account.owner == &ID && data.len() >= 8 && data[..8] == Category::DISCRIMINATOR,
+            DocumentError::UnknownCategory
+        );
+        Ok(account.key())
+    }
+}
+
+/// A group of documents, such as a legal case, owned by `authority`.
+#[account]
+pub struct Collection {
+    pub authority: Pubkey,
+    pub name: String,
+    pub documents_in_collection: u64,
+    pub created_at: i64,
+}
+
+impl Collection {
+    pub const LEN: usize = 8 + 32 + (4 + MAX_COLLECTION_NAME_LEN) + 8 + 8;
+}
+
+/// A company on whose behalf documents are registered and owned.
+#[account]
+pub struct Organization {
+    pub admin: Pubkey,
+    pub name: String,
+    pub member_count: u32,
+    pub created_at: i64,
+}
+
+impl Organization {
+    pub const LEN: usize = 8 + 32 + (4 + MAX_ORG_NAME_LEN) + 4 + 8;
+}
+
+/// `member`'s role in `organization`.
+#[account]
+pub struct OrgMember {
+    pub organization: Pubkey,
+    pub member: Pubkey,
+    pub role: OrgRole,
+    pub added_at: i64,
+}
+
+impl OrgMember {
+    pub const LEN: usize = 8 + 32 + 32 + 1 + 8;
+}
+
+#[account]
+pub struct Document {
+    pub authority: Pubkey,
+    pub document_hash: String,
+    pub document_name: String,
+    pub document_type: String,
+    /// Time of the last content or metadata change
+    pub updated_at: i64,
+    pub status: DocumentStatus,
+    pub version: u32,
+    pub signatures_count: u64,
+    pub expires_at: Option<i64>,
+    pub pending_owner: Option<Pubkey>,
+    pub hash_algorithm: HashAlgorithm,
+    pub revocation_reason: Option<String>,
+    pub required_signers: Vec<Pubkey>,
+    pub signature_threshold: u8,
+    pub content_length: u64,
+    pub mime_type: String,
+    pub delegate: Option<Pubkey>,
+    pub previous_owners: Vec<Pubkey>,
+    pub metadata: Option<DocumentMetadata>,
+    pub storage_uri: Option<String>,
+    pub category: Option<Pubkey>,
+    /// Registration time, never changed afterwards. Appended rather than
+    /// placed next to `updated_at` so migrated accounts keep their layout.
+    pub created_at: i64,
+    /// Document this one amends or supplements
+    pub parent: Option<Pubkey>,
+    /// Count of the registering authority's documents at registration, so
+    /// the address is `["document", registrant, index]`
+    pub index: u64,
+    /// `document_hash` decoded to bytes, for 32-byte digests
+    pub document_hash_bytes: Option<[u8; 32]>,
+    /// Who decides a document registered with `requires_review`
+    pub reviewer: Option<Pubkey>,
+    pub rejection_reason: Option<String>,
+    pub notarizations_count: u64,
+    /// Set while a `Dispute` is open; blocks transfers and closing
+    pub disputed: bool,
+    /// Set by the manager authority; blocks every mutation
+    pub legal_hold: bool,
+    pub legal_hold_by: Option<Pubkey>,
+    pub legal_hold_at: Option<i64>,
+    /// Set by the document authority; blocks changes without touching status
+    pub locked: bool,
+    /// Slot of registration, a monotonic ordering independent of wall-clock time
+    pub slot: u64,
+    /// Wallet that registered the document, whose `AuthorityStats` counts it
+    pub registrant: Pubkey,
+    /// Signers who must approve one after another, in this order
+    pub signer_sequence: Vec<Pubkey>,
+    /// Number of open `DocumentTag` accounts
+    pub tags_count: u8,
+    /// Time from which signing fails and `crank_expire` may run
+    pub signing_deadline: Option<i64>,
+    /// Key allowed to call `transfer_ownership` for the authority
+    pub approved_operator: Option<Pubkey>,
+    pub collection: Option<Pubkey>,
+    /// Concurrent Merkle tree holding compressed signatures
+    pub signature_tree: Option<Pubkey>,
+    /// Leaves appended to `signature_tree`
+    pub compressed_signatures_count: u64,
+    /// Content key wrapped for the authority, for confidential documents
+    pub encrypted_key: Option<Vec<u8>>,
+    /// Bump of `["document", registrant, index]`
+    pub bump: u8,
+    /// Layout version, see `CURRENT_SCHEMA_VERSION`
+    pub schema_version: u8,
+    /// Positions in `signer_sequence` before the latest approval whose
+    /// approval was revoked or pruned, as a bit set
+    pub sequence_gaps: u16,
+    /// `valid_until` of each counted approval that has one, on documents
+    /// with a completion threshold
+    pub approval_expiries: Vec<i64>,
+}
+
+impl Document {
+    /// Fails unless the document's status still allows its content and
+    /// signatures to change.
+    pub fn require_active(&self) -> Result<()> {
+        match self.status {
+            DocumentStatus::Active => Ok(()),
+            DocumentStatus::Revoked => err!(DocumentError::DocumentRevoked),
+            DocumentStatus::PendingReview => err!(DocumentError::DocumentPendingReview),
+            DocumentStatus::Expired => err!(DocumentError::SigningPeriodEnded),
+            DocumentStatus::Archived
+            | DocumentStatus::FullyExecuted
+            | DocumentStatus::Rejected => {
+                err!(DocumentError::DocumentNotActive)
+            }
+        }
+    }
+    
+    /// Number of signatures after which the document is fully executed: the
+    /// whole signer sequence if there is one, otherwise the explicit
+    /// threshold if set, otherwise every required signer.
+    pub fn completion_threshold(&self) -> Option<u64> {
+        if !self.signer_sequence.is_empty() {
+            return Some(self.signer_sequence.len() as u64);
+        }
+        match (self.signature_threshold, self.required_signers.len()) {
+            (0, 0) => None,
+            (0, required) => Some(required as u64),
+            (threshold, _) => Some(threshold as u64),
+        }
+    }
+    
+    /// Approvals that still hold at unix time `now`.
+    pub fn live_approvals(&self, now: i64) -> u64 {
+        let expired = self
+            .approval_expiries
+            .iter()
+            .filter(|valid_until| now >= **valid_until)
+            .count() as u64;
+        self.signatures_count.saturating_sub(expired)
+    }
+    
+    /// Whether the approvals still valid at unix time `now` complete the
+    /// document.
+    pub fn threshold_reached(&self, now: i64) -> bool {
+        matches!(self.completion_threshold(), Some(required) if self.live_approvals(now) >= required)
+    }
+    
+    /// Counts an approval by `signer`, which stops counting at
+    /// `valid_until` if set.
+    pub fn record_approval(&mut self, signer: &Pubkey, valid_until: Option<i64>) -> Result<()> {
+        if let Some(position) = self.sequence_position(signer) {
+            self.sequence_gaps &= !(1 << position);
+        }
+        if let (Some(valid_until), Some(_)) = (valid_until, self.completion_threshold()) {
+            // Only required or sequenced signers count toward a threshold
+            require!(
+                self.approval_expiries.len() < MAX_REQUIRED_SIGNERS,
+                DocumentError::TooManyRequiredSigners
+            );
+            self.approval_expiries.push(valid_until);
+        }
+        self.signatures_count = self
+            .signatures_count
+            .checked_add(1)
+            .ok_or(DocumentError::CounterOverflow)?;
+        Ok(())
+    }
+    
+    /// Stops counting an approval by `signer` that was recorded with
+    /// `valid_until`.
+    pub fn release_approval(&mut self, signer: &Pubkey, valid_until: Option<i64>) -> Result<()> {
+        if let Some(position) = self.sequence_position(signer) {
+            if Some(position) != self.latest_sequence_position() {
+                self.sequence_gaps |= 1 << position;
+            }
+        }
+        if let Some(valid_until) = valid_until {
+            if let Some(i) = self.approval_expiries.iter().position(|e| *e == valid_until) {
+                self.approval_expiries.swap_remove(i);
+            }
+        }
+        self.signatures_count = self
+            .signatures_count
+            .checked_sub(1)
+            .ok_or(DocumentError::CounterUnderflow)?;
+        Ok(())
+    }
+    
+    /// Positions in the signer sequence holding a counted approval, as a
+    /// bit set: every position up to the latest approval except the gaps.
+    pub fn sequence_signed(&self) -> u16 {
+        let reached = self.signatures_count + self.sequence_gaps.count_ones() as u64;
+        let prefix = if reached >= u16::BITS as u64 {
+            u16::MAX
+        } else {
+            (1 << reached) - 1
+        };
+        prefix & !self.sequence_gaps
+    }
+    
+    /// Position of the latest approval in the signer sequence.
+    pub fn latest_sequence_position(&self) -> Option<u64> {
+        (u16::BITS - self.sequence_signed().leading_zeros())
+            .checked_sub(1)
+            .map(u64::from)
+    }
+    
+    /// Changes the m-of-n threshold, which is only allowed before the first
+    /// signature and on documents without a signer sequence.
+    pub fn set_signature_threshold(&mut self, signature_threshold: u8) -> Result<()> {
+        require!(self.signatures_count == 0, DocumentError::ThresholdLocked);
+        require!(
+            self.signer_sequence.is_empty(),
+            DocumentError::InvalidSignerSequence
+        );
+        require!(
+            self.required_signers.is_empty()
+                || signature_threshold as usize <= self.required_signers.len(),
+            DocumentError::InvalidThreshold
+        );
+        
+        self.signature_threshold = signature_threshold;
+        Ok(())
+    }
+    
+    /// Hands the document to `new_owner`, clearing any pending transfer,
+    /// delegate and approved operator, and returns the previous owner. Only the most recent
+    /// `MAX_PREVIOUS_OWNERS` owners are kept.
+    pub fn transfer_to(&mut self, new_owner: Pubkey) -> Pubkey {
+        let old_owner = self.authority;
+        
+        if self.previous_owners.len() >= MAX_PREVIOUS_OWNERS {
+            let excess = self.previous_owners.len() + 1 - MAX_PREVIOUS_OWNERS;
+            self.previous_owners.drain(..excess);
+        }
+        self.previous_owners.push(old_owner);
+        
+        self.authority = new_owner;
+        self.pending_owner = None;
+        self.delegate = None;
+        self.approved_operator = None;
+        
+        old_owner
+    }
+    
+    /// Checks that `signer` may change the document's content: its
+    /// authority, its delegate, or a holder of an `Update` delegation.
+    pub fn check_updater(
+        &self,
+        signer: &Pubkey,
+        delegation: Option<&Account<Delegation>>,
+    ) -> Result<()> {
+        if self.delegate == Some(*signer) {
+            return Ok(());
+        }
+        check_acting_for(&self.authority, signer, delegation, DelegationScope::Update)
+    }
+    
+    /// Returns the key of `account` if it is a document owned by this
+    /// program, and fails with `InvalidParent` otherwise.
+    pub fn check_parent(account: &AccountInfo) -> Result<Pubkey> {
+        let data = account.try_borrow_data()?;
+        require!(
+            account.owner == &ID && data.len() >= 8 && data[..8] == Document::DISCRIMINATOR,
+            DocumentError::InvalidParent
+        );
+        Ok(account.key())
+    }
+    
+    /// Fails unless the document awaits review and `reviewer` is its
+    /// designated reviewer.
+    pub fn require_reviewer(&self, reviewer: &Pubkey) -> Result<()> {
+        require!(
+            self.status == DocumentStatus::PendingReview,
+            DocumentError::NotPendingReview
+        );
+        require!(self.reviewer == Some(*reviewer), DocumentError::NotReviewer);
+        Ok(())
+    }
+    
+    /// Fails while the document is under legal hold or locked by its
+    /// authority.
+    pub fn require_mutable(&self) -> Result<()> {
+        require!(!self.legal_hold, DocumentError::LegalHold);
+        require!(!self.locked, DocumentError::DocumentLocked);
+        Ok(())
+    }
+    
+    /// Fails unless `signer_sequence` is a valid signing order, which
+    /// replaces `required_signers` and the threshold rather than combining
+    /// with them.
+    pub fn validate_signer_sequence(
+        signer_sequence: &[Pubkey],
+        required_signers: &[Pubkey],
+        signature_threshold: u8,
+    ) -> Result<()> {
+        if signer_sequence.is_empty() {
+            return Ok(());
+        }
+        require!(
+            signer_sequence.len() <= MAX_REQUIRED_SIGNERS,
+            DocumentError::TooManyRequiredSigners
+        );
+        require!(
+            required_signers.is_empty() && signature_threshold == 0,
+            DocumentError::InvalidSignerSequence
+        );
+        for (i, signer) in signer_sequence.iter().enumerate() {
+            require!(
+                !signer_sequence[..i].contains(signer),
+                DocumentError::DuplicateSigner
+            );
+        }
+        Ok(())
+    }
+    
+    /// Position of `signer` in the signer sequence.
+    pub fn sequence_position(&self, signer: &Pubkey) -> Option<u64> {
+        self.signer_sequence
+            .iter()
+            .position(|s| s == signer)
+            .map(|position| position as u64)
+    }
+    
+    /// Fails unless `signer` is next in the signer sequence: the first
+    /// position without an approval. Documents without a sequence accept
+    /// any order.
+    pub fn check_sequence(&self, signer: &Pubkey) -> Result<()> {
+        if self.signer_sequence.is_empty() {
+            return Ok(());
+        }
+        match self.sequence_position(signer) {
+            None => err!(DocumentError::SignerNotAuthorized),
+            Some(position) if position != u64::from(self.sequence_signed().trailing_ones()) => {
+                err!(DocumentError::OutOfOrderSignature)
+            }
+            Some(_) => Ok(()),
+        }
+    }
+    
+    /// Whether the signing deadline has been reached at unix time `now`.
+    pub fn signing_period_ended(&self, now: i64) -> bool {
+        matches!(self.signing_deadline, Some(deadline) if now >= deadline)
+    }
+    
+    /// Whether the document's validity has ended at unix time `now`.
+    pub fn is_expired(&self, now: i64) -> bool {
+        matches!(self.expires_at, Some(expires_at) if now >= expires_at)
+    }
+    
+    /// Moves the expiry to `expires_at`, which must be later than the
+    /// current one. Expiry never moves earlier.
+    pub fn extend_expiry(&mut self, expires_at: i64) -> Result<()> {
+        // A document without an expiry never expires, so any deadline would shorten it
+        let current = self.expires_at.ok_or(DocumentError::ExpiryNotExtended)?;
+        require!(expires_at > current, DocumentError::ExpiryNotExtended);
+        
+        self.expires_at = Some(expires_at);
+        Ok(())
+    }
+    
+    pub const LEN: usize = 8
+        + 32
+        + (4 + MAX_HASH_LEN)
+        + (4 + MAX_NAME_LEN)
+        + (4 + MAX_DOC_TYPE_LEN)
+        + 8
+        + 1
+        + 4
+        + 8
+        + (1 + 8)
+        + (1 + 32)
+        + 1
+        + (1 + 4 + MAX_REVOCATION_REASON_LEN)
+        + (4 + 32 * MAX_REQUIRED_SIGNERS)
+        + 1
+        + 8
+        + (4 + MAX_MIME_TYPE_LEN)
+        + (1 + 32)
+        + (4 + 32 * MAX_PREVIOUS_OWNERS)
+        + (1 + DocumentMetadata::LEN)
+        + (1 + 4 + MAX_STORAGE_URI_LEN)
+        + (1 + 32)
+        + 8
+        + (1 + 32)
+        + 8
+        + (1 + 32)
+        + (1 + 32)
+        + (1 + 4 + MAX_REVOCATION_REASON_LEN)
+        + 8
+        + 1
+        + 1
+        + (1 + 32)
+        + (1 + 8)
+        + 1
+        + 8
+        + 32
+        + (4 + 32 * MAX_REQUIRED_SIGNERS)
+        + 1
+        + (1 + 8)
+        + (1 + 32)
+        + (1 + 32)
+        + (1 + 32)
+        + 8
+        + (1 + 4 + MAX_ENCRYPTED_KEY_LEN)
+        + 1
+        + 1
+        + 2
+        + (4 + 8 * MAX_REQUIRED_SIGNERS);
+}
+
+/// Root of a Merkle tree over `leaf_count` off-chain document hashes.
+#[account]
+pub struct MerkleBatch {
+    pub authority: Pubkey,
+    pub merkle_root: [u8; 32],
+    pub leaf_count: u32,
+    pub created_at: i64,
+    /// Free-form reference to the off-chain manifest
+    pub description: String,
+}
+
+impl MerkleBatch {
+    pub const LEN: usize = 8 + 32 + 32 + 4 + 8 + (4 + MAX_MERKLE_DESCRIPTION_LEN);
+}
+
+/// Record of one `update_document` call. `document` is the first field, so
+/// a memcmp filter at offset 8 lists a document's full history.
+#[account]
+pub struct DocumentVersion {
+    pub document: Pubkey,
+    pub previous_hash: String,
+    pub hash: String,
+    pub version: u32,
+    pub timestamp: i64,
+    pub updated_by: Pubkey,
+}
+
+impl DocumentVersion {
+    pub const LEN: usize = 8 + 32 + (4 + MAX_HASH_LEN) + (4 + MAX_HASH_LEN) + 4 + 8 + 32;
+}
+
+/// Records that `grantee` is authorized to view `document`.
+#[account]
+pub struct AccessGrant {
+    pub document: Pubkey,
+    pub grantee: Pubkey,
+    pub granted_at: i64,
+    pub expires_at: Option<i64>,
+    /// Content key wrapped for `grantee`
+    pub wrapped_key: Option<Vec<u8>>,
+}
+
+impl AccessGrant {
+    pub const LEN: usize = 8 + 32 + 32 + 8 + (1 + 8) + (1 + 4 + MAX_ENCRYPTED_KEY_LEN);
+}
+
+/// One key-value attribute of a document. `document` is the first field, so
+/// a memcmp filter at offset 8 lists all attributes of a document.
+#[account]
+pub struct DocumentAttribute {
+    pub document: Pubkey,
+    pub key: String,
+    pub value: String,
+}
+
+impl DocumentAttribute {
+    pub const LEN: usize = 8 + 32 + (4 + MAX_ATTRIBUTE_KEY_LEN) + (4 + MAX_ATTRIBUTE_VALUE_LEN);
+}
+
+/// Places a document under a tag. `tag_hash` is the first field, so a
+/// memcmp filter at offset 8 lists all documents carrying a tag.
+#[account]
+pub struct DocumentTag {
+    /// SHA-256 of `tag`
+    pub tag_hash: [u8; 32],
+    pub document: Pubkey,
+    pub tag: String,
+    pub created_at: i64,
+}
+
+impl DocumentTag {
+    pub const LEN: usize = 8 + 32 + 32 + (4 + MAX_TAG_LEN) + 8;
+}
+
+/// A directed relationship from `source` to `target`.
+#[account]
+pub struct DocumentLink {
+    pub source: Pubkey,
+    pub target: Pubkey,
+    pub relation: DocumentRelation,
+    pub created_at: i64,
+}
+
+impl DocumentLink {
+    pub const LEN: usize = 8 + 32 + 32 + 1 + 8;
+}
+
+#[account]
+pub struct DocumentSignature {
+    pub document: Pubkey,
+    pub signer: Pubkey,
+    pub signature_hash: String,
+    pub timestamp: i64,
+    pub kind: SignatureKind,
+    pub document_version: u32,
+    /// Slot the signature was recorded in
+    pub slot: u64,
+    /// Capacity the signer signed in, such as "Witness" or "CEO"
+    pub role: Option<String>,
+    /// Hash of an off-chain comment left with the signature
+    pub comment_hash: Option<String>,
+    /// Time from which the signature no longer counts and may be pruned
+    pub valid_until: Option<i64>,
+    pub bump: u8,
+    pub schema_version: u8,
+}
+
+impl DocumentSignature {
+    pub const LEN: usize = 8
+        + 32
+        + 32
+        + (4 + MAX_SIGNATURE_HASH_LEN)
+        + 8
+        + 1
+        + 4
+        + 8
+        + (1 + 4 + MAX_SIGNER_ROLE_LEN)
+        + (1 + 4 + MAX_COMMENT_HASH_LEN)
+        + (1 + 8)
+        + 1
+        + 1;
+    
+    /// Whether the signature still holds at unix time `now`.
+    pub fn is_signature_valid(&self, now: i64) -> bool {
+        !matches!(self.valid_until, Some(valid_until) if now >= valid_until)
+    }
+}
+
+/// An invitation for `signer` to sign `document`, kept after it is answered
+/// until the authority closes it past the deadline.
+#[account]
+pub struct SignatureRequest {
+    pub document: Pubkey,
+    pub signer: Pubkey,
+    pub requested_by: Pubkey,
+    pub deadline: i64,
+    pub status: SignatureRequestStatus,
+    /// Hash of the off-chain reason given when declining
+    pub reason_hash: Option<String>,
+    pub created_at: i64,
+}
+
+impl SignatureRequest {
+    pub const LEN: usize = 8 + 32 + 32 + 32 + 8 + 1 + (1 + 4 + MAX_REASON_HASH_LEN) + 8;
+}
+
+/// Singleton tracking the notaries registered by the manager authority.
+#[account]
+pub struct NotaryRegistry {
+    pub active_notaries: u32,
+}
+
+impl NotaryRegistry {
+    pub const LEN: usize = 8 + 4;
+}
+
+/// A notary known to the registry. Removal only clears `active`, so past
+/// notarizations keep resolving to their notary.
+#[account]
+pub struct NotaryEntry {
+    pub notary: Pubkey,
+    pub active: bool,
+    pub added_at: i64,
+}
+
+impl NotaryEntry {
+    pub const LEN: usize = 8 + 32 + 1 + 8;
+}
+
+/// A notary's seal on a document, separate from the parties' signatures.
+#[account]
+pub struct Notarization {
+    pub document: Pubkey,
+    pub notary: Pubkey,
+    pub seal_hash: String,
+    pub timestamp: i64,
+    pub jurisdiction: Option<String>,
+}
+
+impl Notarization {
+    pub const LEN: usize = 8
+        + 32
+        + 32
+        + (4 + MAX_SIGNATURE_HASH_LEN)
+        + 8
+        + (1 + 4 + MAX_JURISDICTION_LEN);
+    
+    /// Address of `notary`'s notarization of `document`.
+    pub fn address(document: &Pubkey, notary: &Pubkey) -> Pubkey {
+        Pubkey::find_program_address(&[b"notarization", document.as_ref(), notary.as_ref()], &ID).0
+    }
+}
+
+/// A third party's statement about a document, such as an audit finding.
+/// The meaning of `attestation_type` is left to clients.
+#[account]
+pub struct Attestation {
+    pub document: Pubkey,
+    pub attester: Pubkey,
+    pub attestation_type: u8,
+    pub statement_hash: String,
+    pub timestamp: i64,
+}
+
+impl Attestation {
+    pub const LEN: usize = 8 + 32 + 32 + 1 + (4 + MAX_STATEMENT_HASH_LEN) + 8;
+}
+
+/// The open dispute on a document, closed when resolved.
+#[account]
+pub struct Dispute {
+    pub document: Pubkey,
+    pub raised_by: Pubkey,
+    pub reason_hash: String,
+    pub raised_at: i64,
+}
+
+impl Dispute {
+    pub const LEN: usize = 8 + 32 + 32 + (4 + MAX_REASON_HASH_LEN) + 8;
+}
+
+/// Lets `delegate` act for `authority` within `scope` until `expires_at`.
+#[account]
+pub struct Delegation {
+    pub authority: Pubkey,
+    pub delegate: Pubkey,
+    pub expires_at: i64,
+    pub scope: DelegationScope,
+}
+
+impl Delegation {
+    pub const LEN: usize = 8 + 32 + 32 + 8 + 1;
+}
+
+/// Per-authority registration counter used to derive document addresses.
+#[account]
+pub struct AuthorityStats {
+    pub authority: Pubkey,
+    /// Documents ever registered; never decreases, as it seeds document PDAs
+    pub document_count: u64,
+    /// Registered documents not yet closed, checked against the quota
+    pub documents_owned: u64,
+}
+
+impl AuthorityStats {
+    pub const LEN: usize = 8 + 32 + 8 + 8;
+}
+
+/// Resolves a document hash to the document that first registered it.
+#[account]
+pub struct HashIndex {
+    pub document: Pubkey,
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
+
+impl HashIndex {
+    pub const LEN: usize = 8 + 32 + 32 + 8;
+    
+    /// Error for registering this hash again: `DuplicateHash` when the
+    /// original registrant retries, `HashAlreadyRegistered` otherwise.
+    pub fn duplicate_error(&self, authority: &Pubkey) -> DocumentError {
+        if self.authority == *authority {
+            DocumentError::DuplicateHash
+        } else {
+            DocumentError::HashAlreadyRegistered
+        }
+    }
+    
+    /// Address of the index entry for `document_hash`.
+    pub fn address(document_hash: &str) -> Pubkey {
+        Pubkey::find_program_address(&[b"hash", hash(document_hash.as_bytes()).as_ref()], &ID).0
+    }
+}
+
+/// A single entry of `register_documents_batch`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct DocumentInput {
+    pub document_hash: String,
+    pub hash_algorithm: HashAlgorithm,
+    pub document_name: String,
+    pub document_type: String,
+    pub content_length: u64,
+    pub mime_type: String,
+    pub metadata: Option<DocumentMetadata>,
+    pub expires_at: Option<i64>,
+    pub required_signers: Vec<Pubkey>,
+    pub signature_threshold: u8,
+    pub signer_sequence: Vec<Pubkey>,
+    pub signing_deadline: Option<i64>,
+    pub storage_uri: Option<String>,
+    /// Must be the batch's `category` account when set
+    pub category: Option<Pubkey>,
+    /// Must be the batch's `parent` account when set
+    pub parent: Option<Pubkey>,
+}
+
+impl DocumentInput {
+    fn validate(&self) -> Result<()> {
+        require!(self.document_hash.len() <= MAX_HASH_LEN, DocumentError::HashTooLong);
+        require!(self.document_name.len() <= MAX_NAME_LEN, DocumentError::NameTooLong);
+        require!(self.document_type.len() <= MAX_DOC_TYPE_LEN, DocumentError::TypeTooLong);
+        validate_hash(&self.document_hash, self.hash_algorithm)?;
+        validate_signing_rules(
+            &self.required_signers,
+            self.signature_threshold,
+            &self.signer_sequence,
+        )?;
+        validate_mime_type(&self.mime_type)?;
+        if let Some(storage_uri) = &self.storage_uri {
+            validate_storage_uri(storage_uri)?;
+        }
+        if let Some(metadata) = &self.metadata {
+            metadata.validate()?;
+        }
+        Ok(())
+    }
+}
+
+/// Return data of `get_document`, Borsh-encoded in field order.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct DocumentSummary {
+    pub document_hash: String,
+    pub status: DocumentStatus,
+    pub version: u32,
+    pub signatures_count: u64,
+    pub notarizations_count: u64,
+}
+
+/// Return data of `get_manager_stats`, Borsh-encoded in field order.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct ManagerStats {
+    pub authority: Pubkey,
+    pub paused: bool,
+    pub document_count: u64,
+    pub archived_count: u64,
+    pub total_signatures: u64,
+    pub last_activity_ts: i64,
+}
+
+/// Typed descriptive fields stored on a `Document`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct DocumentMetadata {
+    pub title: String,
+    pub author: String,
+    pub tags: Vec<String>,
+    pub created_at: i64,
+}
+
+impl DocumentMetadata {
+    pub const LEN: usize = (4 + MAX_TITLE_LEN)
+        + (4 + MAX_AUTHOR_LEN)
+        + (4 + MAX_TAGS * (4 + MAX_TAG_LEN))
+        + 8;
+    
+    fn validate(&self) -> Result<()> {
+        require!(self.title.len() <= MAX_TITLE_LEN, DocumentError::TitleTooLong);
+        require!(self.author.len() <= MAX_AUTHOR_LEN, DocumentError::AuthorTooLong);
+        require!(self.tags.len() <= MAX_TAGS, DocumentError::TooManyTags);
+        require!(
+            self.tags.iter().all(|tag| tag.len() <= MAX_TAG_LEN),
+            DocumentError::TagTooLong
+        );
+        Ok(())
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum RegistrationMode {
+    Open,
+    Allowlisted,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum OrgRole {
+    Admin,
+    Registrar,
+    Signer,
+    Viewer,
+}
+
+/// How the source of a `DocumentLink` relates to its target.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum DocumentRelation {
+    Supersedes,
+    References,
+    AmendmentOf,
+}
+
+/// Outcome of `resolve_dispute`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum DisputeResolution {
+    Upheld,
+    Dismissed,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum DocumentStatus {
+    Active,
+    Archived,
+    Revoked,
+    FullyExecuted,
+    Rejected,
+    PendingReview,
+    /// The signing deadline passed before the document was fully executed
+    Expired,
+}
+
+/// Actions a delegation can cover. Ownership transfers are never delegable.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum DelegationScope {
+    Update,
+    Archive,
+    Sign,
+    All,
+}
+
+impl DelegationScope {
+    pub fn permits(&self, required: DelegationScope) -> bool {
+        *self == DelegationScope::All || *self == required
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum SignatureRequestStatus {
+    Pending,
+    Fulfilled,
+    Declined,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum SignatureKind {
+    Approve,
+    Reject,
+    Witness,
+    Acknowledge,
+}
+
+impl SignatureKind {
+    /// Whether this kind of signature decides the document's outcome.
+    pub fn is_decision(&self) -> bool {
+        matches!(self, SignatureKind::Approve | SignatureKind::Reject)
+    }
+}
+
+/// Digest algorithm that produced a document hash. `Sha256` is the first
+/// variant so zeroed data from before the field existed decodes as SHA-256.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HashAlgorithm {
+    #[default]
+    Sha256,
+    Sha512,
+    Blake3,
+    Keccak256,
+}
+
+impl HashAlgorithm {
+    /// Length of a digest produced by this algorithm, in hex characters.
+    pub fn hex_len(&self) -> usize {
+        match self {
+            HashAlgorithm::Sha256 => 64,
+            HashAlgorithm::Sha512 => 128,
+            HashAlgorithm::Blake3 => 64,
+            HashAlgorithm::Keccak256 => 64,
+        }
+    }
+}
+
+#[error_code]
+pub enum DocumentError {
+    #[msg("You are not authorized to perform this action")]
+    Unauthorized,
+    #[msg("The document has been revoked")]
+    DocumentRevoked,
+    #[msg("Active documents can only be closed with force")]
+    DocumentActive,
+    #[msg("The document has expired")]
+    DocumentExpired,
+    #[msg("The document is not active")]
+    DocumentNotActive,
+    #[msg("The document already belongs to this authority")]
+    SelfTransfer,
+    #[msg("There is no pending ownership transfer")]
+    NoPendingTransfer,
+    #[msg("Document hash must be lowercase hex of the length produced by its hash algorithm")]
+    InvalidHashFormat,
+    #[msg("Document hash exceeds MAX_HASH_LEN")]
+    HashTooLong,
+    #[msg("Document name exceeds MAX_NAME_LEN")]
+    NameTooLong,
+    #[msg("Document type exceeds MAX_DOC_TYPE_LEN")]
+    TypeTooLong,
+    #[msg("Signature hash exceeds MAX_SIGNATURE_HASH_LEN")]
+    SignatureHashTooLong,
+    #[msg("Revocation reason exceeds MAX_REVOCATION_REASON_LEN")]
+    ReasonTooLong,
+    #[msg("This document hash has already been registered")]
+    HashAlreadyRegistered,
+    #[msg("The new expiry must be later than the current one")]
+    ExpiryNotExtended,
+    #[msg("Too many required signers")]
+    TooManyRequiredSigners,
+    #[msg("Threshold exceeds the number of required signers")]
+    InvalidThreshold,
+    #[msg("Signer is not one of the document's required signers")]
+    SignerNotAuthorized,
+    #[msg("The document is already archived")]
+    AlreadyArchived,
+    #[msg("The document is not archived")]
+    DocumentNotArchived,
+    #[msg("MIME type is not in ALLOWED_MIME_TYPES")]
+    UnsupportedMimeType,
+    #[msg("Batch exceeds MAX_BATCH_SIZE documents")]
+    BatchTooLarge,
+    #[msg("This authority has already registered this document hash")]
+    DuplicateHash,
+    #[msg("The signature threshold cannot change once the document has signatures")]
+    ThresholdLocked,
+    #[msg("This signer has already signed the document")]
+    AlreadySigned,
+    #[msg("Signatures can only be revoked while the document is active")]
+    SignatureLocked,
+    #[msg("The signature belongs to a different document")]
+    SignatureDocumentMismatch,
+    #[msg("The signature was made on an earlier version of the document")]
+    SignatureOutdated,
+    #[msg("No ed25519 instruction proves the signer signed the document hash")]
+    InvalidSignature,
+    #[msg("The program is paused")]
+    ProgramPaused,
+    #[msg("The delegation has expired")]
+    DelegationExpired,
+    #[msg("The delegation does not cover this action")]
+    DelegationScopeMismatch,
+    #[msg("Metadata title is too long")]
+    TitleTooLong,
+    #[msg("Metadata author is too long")]
+    AuthorTooLong,
+    #[msg("Too many metadata tags")]
+    TooManyTags,
+    #[msg("A metadata tag is too long")]
+    TagTooLong,
+    #[msg("The caller is not an allowlisted registrar")]
+    RegistrarNotAllowed,
+    #[msg("The treasury does not hold enough lamports")]
+    InsufficientTreasuryBalance,
+    #[msg("Storage URI must be a valid ipfs:// or ar:// reference")]
+    InvalidStorageUri,
+    #[msg("Category name is too long")]
+    CategoryNameTooLong,
+    #[msg("The category does not exist")]
+    UnknownCategory,
+    #[msg("The new hash equals the current hash")]
+    HashUnchanged,
+    #[msg("A revoked document cannot be unarchived")]
+    CannotUnarchiveRevoked,
+    #[msg("Attribute key is too long")]
+    AttributeKeyTooLong,
+    #[msg("Attribute value is too long")]
+    AttributeValueTooLong,
+    #[msg("The parent is not a document of this program")]
+    InvalidParent,
+    #[msg("A Merkle batch must contain at least one leaf")]
+    EmptyMerkleBatch,
+    #[msg("The Merkle proof does not lead to the batch root")]
+    InvalidProof,
+    #[msg("The payer cannot cover the registration fee")]
+    InsufficientFee,
+    #[msg("The token account does not hold the fee mint")]
+    InvalidFeeMint,
+    #[msg("The fee must be paid into a token account owned by the treasury")]
+    InvalidTreasuryTokenAccount,
+    #[msg("Only 32-byte digests can be stored as bytes")]
+    HashNotConvertible,
+    #[msg("A reviewer must be named when review is required")]
+    ReviewerRequired,
+    #[msg("Document is not pending review")]
+    NotPendingReview,
+    #[msg("Only the designated reviewer can decide this document")]
+    NotReviewer,
+    #[msg("Document is pending review")]
+    DocumentPendingReview,
+    #[msg("The notary is not active")]
+    NotaryNotActive,
+    #[msg("The notary is already active")]
+    NotaryAlreadyActive,
+    #[msg("Statement hash is too long")]
+    StatementHashTooLong,
+    #[msg("The Merkle proof is longer than MAX_MERKLE_PROOF_LEN")]
+    ProofTooLong,
+    #[msg("Jurisdiction is too long")]
+    JurisdictionTooLong,
+    #[msg("Dispute reason hash is too long")]
+    ReasonHashTooLong,
+    #[msg("Document is under dispute")]
+    DocumentDisputed,
+    #[msg("Document is under legal hold")]
+    LegalHold,
+    #[msg("Document is not under legal hold")]
+    NotOnLegalHold,
+    #[msg("Document is locked")]
+    DocumentLocked,
+    #[msg("Document is not locked")]
+    DocumentNotLocked,
+    #[msg("Organization name is too long")]
+    OrgNameTooLong,
+    #[msg("The member's organization role does not allow this action")]
+    InsufficientOrgRole,
+    #[msg("A document counter overflowed")]
+    CounterOverflow,
+    #[msg("Too many co-signers in one instruction")]
+    TooManyCoSigners,
+    #[msg("A signer appears more than once")]
+    DuplicateSigner,
+    #[msg("A Merkle batch cannot hold more leaves than a proof can cover")]
+    MerkleBatchTooLarge,
+    #[msg("Merkle batch description is too long")]
+    DescriptionTooLong,
+    #[msg("Leaf index is outside the batch")]
+    LeafIndexOutOfRange,
+    #[msg("The authority has reached its document quota")]
+    AuthorityQuotaExceeded,
+    #[msg("Signer role is too long")]
+    SignerRoleTooLong,
+    #[msg("Comment hash is too long")]
+    CommentHashTooLong,
+    #[msg("Signer is in the sequence but it is not their turn")]
+    OutOfOrderSignature,
+    #[msg("A signer sequence cannot be combined with required signers or a threshold")]
+    InvalidSignerSequence,
+    #[msg("The document already has the maximum number of tags")]
+    TooManyDocumentTags,
+    #[msg("The deadline must be in the future")]
+    InvalidDeadline,
+    #[msg("The signature request has already been answered")]
+    SignatureRequestNotPending,
+    #[msg("The signature request's deadline has passed")]
+    SignatureRequestPastDeadline,
+    #[msg("The signature request's deadline has not passed yet")]
+    SignatureRequestNotExpired,
+    #[msg("The document's signing period has ended")]
+    SigningPeriodEnded,
+    #[msg("The document's signing deadline has not passed yet")]
+    SigningPeriodOpen,
+    #[msg("The document has no signing deadline")]
+    NoSigningDeadline,
+    #[msg("The signature has not expired")]
+    SignatureStillValid,
+    #[msg("The document has no approved operator")]
+    NoApprovedOperator,
+    #[msg("Collection name is too long")]
+    CollectionNameTooLong,
+    #[msg("The signer is not the collection authority")]
+    NotCollectionAuthority,
+    #[msg("The document is not in this collection")]
+    DocumentNotInCollection,
+    #[msg("The source and destination collections are the same")]
+    SameCollection,
+    #[msg("The document already has a signature tree")]
+    SignatureTreeExists,
+    #[msg("The tree is not the document's signature tree")]
+    SignatureTreeMismatch,
+    #[msg("Encrypted key exceeds MAX_ENCRYPTED_KEY_LEN")]
+    KeyTooLarge,
+    #[msg("The document's bump is already recorded")]
+    BumpAlreadySet,
+    #[msg("The treasury account is not the manager's treasury")]
+    InvalidTreasury,
+    #[msg("A counter would drop below zero")]
+    CounterUnderflow,
+    #[msg("Every approval must be passed to reset the signatures")]
+    SignaturesNotReset,
+}
+
+// Events
+#[event]
+pub struct ManagerAuthorityProposed {
+    pub authority: Pubkey,
+    pub pending_authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ManagerAuthorityTransferred {
+    pub old_authority: Pubkey,
+    pub new_authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ProgramPaused {
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ProgramUnpaused {
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CollectionCreated {
+    pub collection: Pubkey,
+    pub authority: Pubkey,
+    pub name: String,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DocumentMoved {
+    pub document_id: Pubkey,
+    pub from_collection: Pubkey,
+    pub to_collection: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CategoryCreated {
+    pub category: Pubkey,
+    pub name: String,
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct OrganizationCreated {
+    pub organization: Pubkey,
+    pub admin: Pubkey,
+    pub name: String,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MemberAdded {
+    pub organization: Pubkey,
+    pub member: Pubkey,
+    pub role: OrgRole,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MemberRemoved {
+    pub organization: Pubkey,
+    pub member: Pubkey,
+    pub role: OrgRole,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RegistrarAdded {
+    pub registrar: Pubkey,
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RegistrarRemoved {
+    pub registrar: Pubkey,
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DocumentRegistered {
+    pub document_id: Pubkey,
+    pub authority: Pubkey,
+    pub document_hash: String,
+    pub hash_algorithm: HashAlgorithm,
+    pub timestamp: i64,
+    pub slot: u64,
+    pub expires_at: Option<i64>,
+    /// Fee charged, in lamports or in tokens of `fee_mint`
+    pub fee: u64,
+    pub fee_mint: Option<Pubkey>,
+    pub storage_uri: Option<String>,
+    pub parent: Option<Pubkey>,
+}
+
+#[event]
+pub struct MerkleBatchRegistered {
+    pub merkle_batch: Pubkey,
+    pub authority: Pubkey,
+    pub merkle_root: [u8; 32],
+    pub leaf_count: u32,
+    pub description: String,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DocumentUpdated {
+    pub document_id: Pubkey,
+    pub authority: Pubkey,
+    pub document_hash: String,
+    pub hash_algorithm: HashAlgorithm,
+    pub version: u32,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DocumentRenamed {
+    pub document_id: Pubkey,
+    pub old_name: String,
+    pub new_name: String,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DocumentMigrated {
+    pub document_id: Pubkey,
+    pub from_version: u8,
+    pub to_version: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct VersionRecorded {
+    pub document_id: Pubkey,
+    pub version_id: Pubkey,
+    pub version: u32,
+    pub previous_hash: String,
+    pub hash: String,
+    pub updated_by: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DocumentRecategorized {
+    pub document_id: Pubkey,
+    pub old_category: Option<Pubkey>,
+    pub new_category: Option<Pubkey>,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AccessGranted {
+    pub document_id: Pubkey,
+    pub grantee: Pubkey,
+    pub expires_at: Option<i64>,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct EncryptedKeyUpdated {
+    pub document_id: Pubkey,
+    /// Set when the key wrapped for a grantee changed
+    pub grantee: Option<Pubkey>,
+    pub cleared: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AccessRevoked {
+    pub document_id: Pubkey,
+    pub grantee: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AttributeSet {
+    pub document_id: Pubkey,
+    pub key: String,
+    pub value: String,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AttributeRemoved {
+    pub document_id: Pubkey,
+    pub key: String,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TagAdded {
+    pub document_id: Pubkey,
+    pub tag: String,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TagRemoved {
+    pub document_id: Pubkey,
+    pub tag: String,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct StorageUriUpdated {
+    pub document_id: Pubkey,
+    pub authority: Pubkey,
+    pub storage_uri: Option<String>,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct NotaryAdded {
+    pub notary: Pubkey,
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct NotaryRemoved {
+    pub notary: Pubkey,
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DocumentNotarized {
+    pub document_id: Pubkey,
+    pub notary: Pubkey,
+    pub notarization_id: Pubkey,
+    pub seal_hash: String,
+    pub jurisdiction: Option<String>,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DocumentAttested {
+    pub document_id: Pubkey,
+    pub attester: Pubkey,
+    pub attestation_id: Pubkey,
+    pub attestation_type: u8,
+    pub statement_hash: String,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AttestationRevoked {
+    pub document_id: Pubkey,
+    pub attester: Pubkey,
+    pub attestation_id: Pubkey,
+    pub attestation_type: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DisputeRaised {
+    pub document_id: Pubkey,
+    pub raised_by: Pubkey,
+    pub reason_hash: String,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DisputeResolved {
+    pub document_id: Pubkey,
+    pub raised_by: Pubkey,
+    pub resolved_by: Pubkey,
+    pub resolution: DisputeResolution,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct LegalHoldPlaced {
+    pub document_id: Pubkey,
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct LegalHoldReleased {
+    pub document_id: Pubkey,
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DocumentLocked {
+    pub document_id: Pubkey,
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DocumentUnlocked {
+    pub document_id: Pubkey,
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DocumentVerified {
+    pub document_id: Pubkey,
+    pub matched: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DocumentSigned {
+    pub document_id: Pubkey,
+    pub signer: Pubkey,
+    pub signature_id: Pubkey,
+    pub kind: SignatureKind,
+    pub document_version: u32,
+    pub verified: bool,
+    pub timestamp: i64,
+    pub slot: u64,
+    pub role: Option<String>,
+    pub comment_hash: Option<String>,
+}
+
+#[event]
+pub struct SignatureRevoked {
+    pub document_id: Pubkey,
+    pub signer: Pubkey,
+    pub signature_id: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SigningPeriodExpired {
+    pub document_id: Pubkey,
+    pub signing_deadline: i64,
+    pub signatures_count: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CompressedSignatureAppended {
+    pub document_id: Pubkey,
+    pub signer: Pubkey,
+    pub signature_hash: String,
+    pub leaf: [u8; 32],
+    pub leaf_index: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SignatureExpired {
+    pub document_id: Pubkey,
+    pub signer: Pubkey,
+    pub signature_id: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SignatureRequested {
+    pub document_id: Pubkey,
+    pub request_id: Pubkey,
+    pub signer: Pubkey,
+    pub requested_by: Pubkey,
+    pub deadline: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SignatureDeclined {
+    pub document_id: Pubkey,
+    pub request_id: Pubkey,
+    pub signer: Pubkey,
+    pub reason_hash: Option<String>,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SignatureRequestExpired {
+    pub document_id: Pubkey,
+    pub request_id: Pubkey,
+    pub signer: Pubkey,
+    /// Status the request was in when closed
+    pub status: SignatureRequestStatus,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DocumentFullyExecuted {
+    pub document_id: Pubkey,
+    pub signatures_count: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DocumentsLinked {
+    pub link_id: Pubkey,
+    pub source: Pubkey,
+    pub target: Pubkey,
+    pub relation: DocumentRelation,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DocumentsUnlinked {
+    pub link_id: Pubkey,
+    pub source: Pubkey,
+    pub target: Pubkey,
+    pub relation: DocumentRelation,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DocumentArchived {
+    pub document_id: Pubkey,
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DocumentUnarchived {
+    pub document_id: Pubkey,
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct OwnershipTransferred {
+    pub document_id: Pubkey,
+    pub old_owner: Pubkey,
+    pub new_owner: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TransferProposed {
+    pub document_id: Pubkey,
+    pub authority: Pubkey,
+    pub pending_owner: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TransferAccepted {
+    pub document_id: Pubkey,
+    pub old_owner: Pubkey,
+    pub new_owner: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TransferCancelled {
+    pub document_id: Pubkey,
+    pub authority: Pubkey,
+    pub pending_owner: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct OperatorApproved {
+    pub document_id: Pubkey,
+    pub authority: Pubkey,
+    pub operator: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ApprovalRevoked {
+    pub document_id: Pubkey,
+    pub authority: Pubkey,
+    pub operator: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DelegateSet {
+    pub document_id: Pubkey,
+    pub authority: Pubkey,
+    pub delegate: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DelegateRevoked {
+    pub document_id: Pubkey,
+    pub authority: Pubkey,
+    pub delegate: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DelegationCreated {
+    pub delegation_id: Pubkey,
+    pub authority: Pubkey,
+    pub delegate: Pubkey,
+    pub expires_at: i64,
+    pub scope: DelegationScope,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DelegationRevoked {
+    pub delegation_id: Pubkey,
+    pub authority: Pubkey,
+    pub delegate: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DocumentClosed {
+    pub document_id: Pubkey,
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DocumentRevoked {
+    pub document_id: Pubkey,
+    pub authority: Pubkey,
+    pub reason: String,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DocumentApproved {
+    pub document_id: Pubkey,
+    pub reviewer: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DocumentRejected {
+    pub document_id: Pubkey,
+    pub reviewer: Pubkey,
+    pub reason: String,
+    pub timestamp: i64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::solana_program::{
+        bpf_loader,
+        entrypoint::{ProgramResult, MAX_PERMITTED_DATA_INCREASE},
+        instruction::{AccountMeta, Instruction},
+        keccak,
+        native_token::LAMPORTS_PER_SOL,
+        program_stubs::{set_syscall_stubs, SyscallStubs},
+        program_utils::limited_deserialize,
+        system_instruction::SystemInstruction,
+    };
+    use anchor_lang::{Discriminator, InstructionData};
+    use std::cell::{Cell, RefCell};
+    use std::collections::HashMap;
+    use std::sync::Once;
+    
+    const START: i64 = 1_700_000_000;
+    
+    thread_local! {
+        static NOW: Cell<i64> = const { Cell::new(START) };
+        static RETURN_DATA: RefCell<Option<Vec<u8>>> = const { RefCell::new(None) };
+    }
+    
+    /// Stands in for the runtime: serves the clock, rent and return data,
+    /// and executes the system program instructions the program invokes
+    struct TestRuntime;
+    
+    impl SyscallStubs for TestRuntime {
+        fn sol_invoke_signed(
+            &self,
+            instruction: &Instruction,
+            account_infos: &[AccountInfo],
+            signers_seeds: &[&[&[u8]]],
+        ) -> ProgramResult {
+            if instruction.program_id == spl_account_compression::ID {
+                return invoke_compression(instruction, account_infos, signers_seeds);
+            }
+            assert_eq!(instruction.program_id, system_program::ID);
+            let account = |i: usize| {
+                account_infos
+                    .iter()
+                    .find(|info| *info.key == instruction.accounts[i].pubkey)
+                    .unwrap()
+            };
+            let signs = |info: &AccountInfo| {
+                info.is_signer
+                    || signers_seeds.iter().any(|seeds| {
+                        Pubkey::create_program_address(seeds, &ID).ok() == Some(*info.key)
+                    })
+            };
+            let allocate = |info: &AccountInfo, space: u64, owner: &Pubkey| {
+                if !signs(info) {
+                    return Err(ProgramError::MissingRequiredSignature);
+                }
+                if !info.data_is_empty() || *info.owner != system_program::ID {
+                    return Err(ProgramError::AccountAlreadyInitialized);
+                }
+                info.realloc(space as usize, true)?;
+                info.assign(owner);
+                Ok(())
+            };
+            let transfer = |from: &AccountInfo, to: &AccountInfo, lamports: u64| {
+                if !signs(from) {
+                    return Err(ProgramError::MissingRequiredSignature);
+                }
+                let remaining = from
+                    .lamports()
+                    .checked_sub(lamports)
+                    .ok_or(ProgramError::InsufficientFunds)?;
+                **from.try_borrow_mut_lamports()? = remaining;
+                **to.try_borrow_mut_lamports()? += lamports;
+                Ok(())
+            };
+            
+            match limited_deserialize(&instruction.data, 1024)
+                .map_err(|_| ProgramError::InvalidInstructionData)?
+            {
+                SystemInstruction::CreateAccount { lamports, space, owner } => {
+                    if account(1).lamports() > 0 {
+                        return Err(ProgramError::AccountAlreadyInitialized);
+                    }
+                    transfer(account(0), account(1), lamports)?;
+                    allocate(account(1), space, &owner)
+                }
+                SystemInstruction::Transfer { lamports } => transfer(account(0), account(1), lamports),
+                SystemInstruction::Allocate { space } => allocate(account(0), space, &system_program::ID),
+                SystemInstruction::Assign { owner } => {
+                    if !signs(account(0)) {
+                        return Err(ProgramError::MissingRequiredSignature);
+                    }
+                    account(0).assign(&owner);
+                    Ok(())
+                }
+                other => panic!("unsupported system instruction {other:?}"),
+            }
+        }
+        
+        fn sol_get_clock_sysvar(&self, var_addr: *mut u8) -> u64 {
+            let clock = Clock {
+                slot: 1,
+                unix_timestamp: NOW.with(Cell::get),
+                ..Clock::default()
+            };
+            unsafe { *(var_addr as *mut Clock) = clock };
+            0
+        }
+        
+        fn sol_get_rent_sysvar(&self, var_addr: *mut u8) -> u64 {
+            unsafe { *(var_addr as *mut Rent) = Rent::default() };
+            0
+        }
+        
+        fn sol_set_return_data(&self, data: &[u8]) {
+            RETURN_DATA.with(|return_data| *return_data.borrow_mut() = Some(data.to_vec()));
+        }
+        
+        fn sol_get_return_data(&self) -> Option<(Pubkey, Vec<u8>)> {
+            RETURN_DATA.with(|return_data| return_data.borrow().clone().map(|data| (ID, data)))
+        }
+    }
+    
+    /// Every leaf appended to a signature tree
+    struct TestTree {
+        depth: u32,
+        leaves: Vec<[u8; 32]>,
+    }
+    
+    thread_local! {
+        static TREES: RefCell<HashMap<Pubkey, TestTree>> = RefCell::new(HashMap::new());
+    }
+    
+    /// Levels of a concurrent Merkle tree of `depth` holding `leaves`, from
+    /// the padded leaves up to the root. Like spl-account-compression, leaves
+    /// are stored as given, empty leaves are zero and parents are
+    /// `keccak(left || right)`.
+    fn compression_levels(leaves: &[[u8; 32]], depth: u32) -> Vec<Vec<[u8; 32]>> {
+        let mut level = leaves.to_vec();
+        level.resize(1 << depth, [0; 32]);
+        let mut levels = vec![level];
+        while levels.last().unwrap().len() > 1 {
+            let parents = levels
+                .last()
+                .unwrap()
+                .chunks(2)
+                .map(|pair| keccak::hashv(&[&pair[0], &pair[1]]).to_bytes())
+                .collect();
+            levels.push(parents);
+        }
+        levels
+    }
+    
+    /// Sibling hashes on the path from leaf `index` to the root
+    fn compression_proof(levels: &[Vec<[u8; 32]>], index: usize) -> Vec<[u8; 32]> {
+        levels[..levels.len() - 1]
+            .iter()
+            .enumerate()
+            .map(|(height, level)| level[(index >> height) ^ 1])
+            .collect()
+    }
+    
+    /// Stands in for spl-account-compression, keeping every leaf so roots
+    /// and proofs can be checked against the whole tree
+    fn invoke_compression(
+        instruction: &Instruction,
+        account_infos: &[AccountInfo],
+        signers_seeds: &[&[&[u8]]],
+    ) -> ProgramResult {
+        let tree = instruction.accounts[0].pubkey;
+        let authority_signs = || {
+            let authority = &instruction.accounts[1].pubkey;
+            account_infos.iter().any(|info| info.key == authority && info.is_signer)
+                || signers_seeds
+                    .iter()
+                    .any(|seeds| Pubkey::create_program_address(seeds, &ID).ok() == Some(*authority))
+        };
+        let (discriminator, mut args) = instruction.data.split_at(8);
+        let invalid = |_| ProgramError::InvalidInstructionData;
+        
+        if discriminator == spl_account_compression::instruction::InitEmptyMerkleTree::DISCRIMINATOR {
+            let init = spl_account_compression::instruction::InitEmptyMerkleTree::deserialize(&mut args)
+                .map_err(invalid)?;
+            if !authority_signs() {
+                return Err(ProgramError::MissingRequiredSignature);
+            }
+            let tree_state = TestTree {
+                depth: init.max_depth,
+                leaves: Vec::new(),
+            };
+            TREES.with(|trees| trees.borrow_mut().insert(tree, tree_state));
+            Ok(())
+        } else if discriminator == spl_account_compression::instruction::Append::DISCRIMINATOR {
+            let append = spl_account_compression::instruction::Append::deserialize(&mut args)
+                .map_err(invalid)?;
+            if !authority_signs() {
+                return Err(ProgramError::MissingRequiredSignature);
+            }
+            TREES.with(|trees| {
+                let mut trees = trees.borrow_mut();
+                let tree = trees.get_mut(&tree).ok_or(ProgramError::UninitializedAccount)?;
+                if tree.leaves.len() >= 1 << tree.depth {
+                    return Err(ProgramError::AccountDataTooSmall);
+                }
+                tree.leaves.push(append.leaf);
+                Ok(())
+            })
+        } else if discriminator == spl_account_compression::instruction::VerifyLeaf::DISCRIMINATOR {
+            let verify = spl_account_compression::instruction::VerifyLeaf::deserialize(&mut args)
+                .map_err(invalid)?;
+            let proof: Vec<[u8; 32]> = instruction.accounts[1..]
+                .iter()
+                .map(|meta| meta.pubkey.to_bytes())
+                .collect();
+            let root = TREES.with(|trees| {
+                let trees = trees.borrow();
+                let tree = trees.get(&tree).ok_or(ProgramError::UninitializedAccount)?;
+                Ok::<_, ProgramError>(compression_levels(&tree.leaves, tree.depth).pop().unwrap()[0])
+            })?;
+            let computed = proof.iter().enumerate().fold(verify.leaf, |node, (height, sibling)| {
+                if verify.index >> height & 1 == 0 {
+                    keccak::hashv(&[&node, sibling]).to_bytes()
+                } else {
+                    keccak::hashv(&[sibling, &node]).to_bytes()
+                }
+            });
+            if computed != verify.root || verify.root != root {
+                return Err(ProgramError::InvalidArgument);
+            }
+            Ok(())
+        } else {
+            panic!("unsupported account compression instruction")
+        }
+    }
+    
+    /// Key of a ledger account, preceded by the original data length that
+    /// `AccountInfo::realloc` reads from just before the key
+    #[repr(C)]
+    struct KeySlot {
+        original_data_len: u32,
+        key: Pubkey,
+    }
+    
+    /// Accounts the program runs against through `entry`. Each account's
+    /// data sits in a buffer laid out like the runtime's input, so `realloc`
+    /// can resize it in place. A failed instruction restores the accounts it
+    /// was given, as the runtime would.
+    struct TestLedger {
+        accounts: HashMap<Pubkey, AccountInfo<'static>>,
+    }
+    
+    impl TestLedger {
+        fn new() -> Self {
+            static INSTALL: Once = Once::new();
+            INSTALL.call_once(|| {
+                set_syscall_stubs(Box::new(TestRuntime));
+            });
+            NOW.with(|now| now.set(START));
+            
+            let mut ledger = Self {
+                accounts: HashMap::new(),
+            };
+            for program in [ID, system_program::ID] {
+                ledger.insert(program, 1, bpf_loader::ID, &[], true);
+            }
+            ledger
+        }
+        
+        fn insert(
+            &mut self,
+            key: Pubkey,
+            lamports: u64,
+            owner: Pubkey,
+            data: &[u8],
+            executable: bool,
+        ) -> AccountInfo<'static> {
+            let slot = Box::leak(Box::new(KeySlot {
+                original_data_len: data.len() as u32,
+                key,
+            }));
+            let buffer: &'static mut [u8] =
+                Box::leak(vec![0; 8 + data.len() + MAX_PERMITTED_DATA_INCREASE].into_boxed_slice());
+            buffer[..8].copy_from_slice(&(data.len() as u64).to_le_bytes());
+            buffer[8..8 + data.len()].copy_from_slice(data);
+            let data = unsafe { std::slice::from_raw_parts_mut(buffer.as_mut_ptr().add(8), data.len()) };
+            
+            let info = AccountInfo::new(
+                &slot.key,
+                false,
+                false,
+                Box::leak(Box::new(lamports)),
+                data,
+                Box::leak(Box::new(owner)),
+                executable,
+                0,
+            );
+            self.accounts.insert(key, info.clone());
+            info
+        }
+        
+        /// Moves the clock `seconds` forward
+        fn advance(&mut self, seconds: i64) {
+            NOW.with(|now| now.set(now.get() + seconds));
+        }
+        
+        /// Funds a wallet owned by the system program
+        fn fund(&mut self, key: Pubkey, lamports: u64) {
+            self.insert(key, lamports, system_program::ID, &[], false);
+        }
+        
+        /// The account at `key`, created empty if it does not exist yet
+        fn info(&mut self, key: &Pubkey) -> AccountInfo<'static> {
+            match self.accounts.get(key) {
+                Some(info) => info.clone(),
+                None => self.insert(*key, 0, system_program::ID, &[], false),
+            }
+        }
+        
+        fn lamports(&mut self, key: &Pubkey) -> u64 {
+            self.info(key).lamports()
+        }
+        
+        /// Whether the program owns an account at `key`
+        fn exists(&mut self, key: &Pubkey) -> bool {
+            *self.info(key).owner == ID
+        }
+        
+        fn load<T: AccountDeserialize>(&mut self, key: &Pubkey) -> T {
+            T::try_deserialize(&mut &self.info(key).data.borrow()[..]).unwrap()
+        }
+        
+        /// Return data set by the last instruction
+        fn return_data(&self) -> Option<Vec<u8>> {
+            RETURN_DATA.with(|return_data| return_data.borrow().clone())
+        }
+        
+        /// Runs one instruction through the program's entrypoint
+        fn send(
+            &mut self,
+            accounts: impl ToAccountMetas,
+            remaining: &[AccountMeta],
+            instruction: impl InstructionData,
+        ) -> std::result::Result<(), ProgramError> {
+            let mut metas = accounts.to_account_metas(None);
+            metas.extend_from_slice(remaining);
+            let infos: Vec<AccountInfo<'static>> = metas
+                .iter()
+                .map(|meta| {
+                    let mut info = self.info(&meta.pubkey);
+                    info.is_signer = meta.is_signer;
+                    info.is_writable = meta.is_writable;
+                    info
+                })
+                .collect();
+            let snapshot: Vec<(u64, Pubkey, Vec<u8>)> = infos
+                .iter()
+                .map(|info| (info.lamports(), *info.owner, info.data.borrow().to_vec()))
+                .collect();
+            RETURN_DATA.with(|return_data| return_data.borrow_mut().take());
+            
+            let result = entry(&ID, &infos, &instruction.data());
+            if result.is_err() {
+                for (info, (lamports, owner, data)) in infos.iter().zip(snapshot) {
+                    **info.lamports.borrow_mut() = lamports;
+                    info.assign(&owner);
+                    info.realloc(data.len(), false).unwrap();
+                    info.data.borrow_mut().copy_from_slice(&data);
+                }
+            }
+            result
+        }
+    }
+    
+    /// The `ProgramError` an instruction fails with when it returns `error`
+    fn program_error(error: impl Into<anchor_lang::error::Error>) -> ProgramError {
+        error.into().into()
+    }
+    
+    fn manager_address() -> Pubkey {
+        Pubkey::find_program_address(&[b"document-manager"], &ID).0
+    }
+    
+    fn stats_address(authority: &Pubkey) -> Pubkey {
+        Pubkey::find_program_address(&[b"authority", authority.as_ref()], &ID).0
+    }
+    
+    fn document_address(registrant: &Pubkey, index: u64) -> Pubkey {
+        Pubkey::find_program_address(&[b"document", registrant.as_ref(), &index.to_le_bytes()], &ID).0
+    }
+    
+    fn hash_index_address(document_hash: &str) -> Pubkey {
+        Pubkey::find_program_address(&[b"hash", hash(document_hash.as_bytes()).as_ref()], &ID).0
+    }
+    
+    /// Lowercase hex SHA-256 digest of `content`
+    fn digest_hex(content: &str) -> String {
+        hash(content.as_bytes()).to_bytes().iter().map(|b| format!("{b:02x}")).collect()
+    }
+    
+    /// A ledger whose manager is run by `admin`, who is funded
+    fn ledger_with_manager(admin: Pubkey) -> TestLedger {
+        let mut ledger = TestLedger::new();
+        ledger.fund(admin, 10 * LAMPORTS_PER_SOL);
+        ledger
+            .send(
+                accounts::Initialize {
+                    document_manager: manager_address(),
+                    authority: admin,
+                    system_program: system_program::ID,
+                },
+                &[],
+                instruction::Initialize {},
+            )
+            .unwrap();
+        ledger
+    }
+    
+    /// A plain registration of a PDF whose content is `content`
+    fn registration(content: &str) -> instruction::RegisterDocument {
+        instruction::RegisterDocument {
+            document_hash: digest_hex(content),
+            document_name: content.to_string(),
+            document_type: "contract".to_string(),
+            expires_at: None,
+            hash_algorithm: HashAlgorithm::Sha256,
+            required_signers: Vec::new(),
+            signature_threshold: 0,
+            content_length: 1024,
+            mime_type: "application/pdf".to_string(),
+            storage_uri: None,
+            requires_review: false,
+            reviewer: None,
+            signer_sequence: Vec::new(),
+            signing_deadline: None,
+        }
+    }
+    
+    /// Registers a document as `authority` and returns its address
+    fn register(
+        ledger: &mut TestLedger,
+        authority: Pubkey,
+        registration: instruction::RegisterDocument,
+    ) -> std::result::Result<Pubkey, ProgramError> {
+        let authority_stats = stats_address(&authority);
+        let index = if ledger.exists(&authority_stats) {
+            ledger.load::<AuthorityStats>(&authority_stats).document_count
+        } else {
+            0
+        };
+        let document = document_address(&authority, index);
+        ledger.send(
+            accounts::RegisterDocument {
+                document_manager: manager_address(),
+                authority_stats,
+                document,
+                hash_index: hash_index_address(&registration.document_hash),
+                category: None,
+                parent: None,
+                treasury: treasury_address(),
+                payer_token_account: None,
+                treasury_token_account: None,
+                token_program: None,
+                allowlist_entry: None,
+                organization: None,
+                org_member: None,
+                collection: None,
+                authority,
+                system_program: system_program::ID,
+            },
+            &[],
+            registration,
+        )?;
+        Ok(document)
+    }
+    
+    fn archive(
+        ledger: &mut TestLedger,
+        document: Pubkey,
+        authority: Pubkey,
+    ) -> std::result::Result<(), ProgramError> {
+        ledger.send(
+            accounts::ArchiveDocument {
+                document_manager: manager_address(),
+                document,
+                authority,
+                delegation: None,
+                organization: None,
+                org_member: None,
+            },
+            &[],
+            instruction::ArchiveDocument {},
+        )
+    }
+    
+    fn close(
+        ledger: &mut TestLedger,
+        document: Pubkey,
+        authority: Pubkey,
+        force: bool,
+    ) -> std::result::Result<(), ProgramError> {
+        let stored = ledger.load::<Document>(&document);
+        ledger.send(
+            accounts::CloseDocument {
+                document,
+                authority_stats: stats_address(&stored.registrant),
+                collection: None,
+                hash_index: hash_index_address(&stored.document_hash),
+                authority,
+            },
+            &[],
+            instruction::CloseDocument { force },
+        )
+    }
+    
+    fn version_address(document: &Pubkey, version: u32) -> Pubkey {
+        Pubkey::find_program_address(&[b"version", document.as_ref(), &version.to_le_bytes()], &ID).0
+    }
+    
+    fn signature_address(document: &Pubkey, signer: &Pubkey) -> Pubkey {
+        Pubkey::find_program_address(&[b"signature", document.as_ref(), signer.as_ref()], &ID).0
+    }
+    
+    /// Records `signer`'s approval of `document` with `sign_document`
+    fn sign(
+        ledger: &mut TestLedger,
+        document: Pubkey,
+        signer: Pubkey,
+    ) -> std::result::Result<(), ProgramError> {
+        ledger.send(
+            accounts::SignDocument {
+                document_manager: manager_address(),
+                document,
+                signature: signature_address(&document, &signer),
+                principal: signer,
+                delegation: None,
+                signature_request: None,
+                signer,
+                system_program: system_program::ID,
+            },
+            &[],
+            instruction::SignDocument {
+                signature_hash: "signature".to_string(),
+                kind: SignatureKind::Approve,
+                role: None,
+                comment_hash: None,
+                valid_until: None,
+            },
+        )
+    }
+    
+    /// Records an approval from each of `signers` in one `sign_document_multi`
+    fn sign_multi(
+        ledger: &mut TestLedger,
+        document: Pubkey,
+        payer: Pubkey,
+        signers: &[Pubkey],
+    ) -> std::result::Result<(), ProgramError> {
+        let remaining: Vec<AccountMeta> = signers
+            .iter()
+            .flat_map(|signer| {
+                [
+                    AccountMeta::new_readonly(*signer, true),
+                    AccountMeta::new(signature_address(&document, signer), false),
+                ]
+            })
+            .collect();
+        ledger.send(
+            accounts::SignDocumentMulti {
+                document_manager: manager_address(),
+                document,
+                payer,
+                system_program: system_program::ID,
+            },
+            &remaining,
+            instruction::SignDocumentMulti {
+                signature_hashes: (0..signers.len()).map(|i| format!("co-signature-{i}")).collect(),
+            },
+        )
+    }
+    
+    /// Replaces the document's content with `content`, keeping its signatures
+    fn update(
+        ledger: &mut TestLedger,
+        document: Pubkey,
+        authority: Pubkey,
+        content: &str,
+    ) -> std::result::Result<(), ProgramError> {
+        let version = ledger.load::<Document>(&document).version;
+        ledger.send(
+            accounts::UpdateDocument {
+                document,
+                document_version: version_address(&document, version + 1),
+                authority,
+                delegation: None,
+                system_program: system_program::ID,
+            },
+            &[],
+            instruction::UpdateDocument {
+                document_hash: digest_hex(content),
+                reset_signatures: false,
+            },
+        )
+    }
+    
+    /// A document with every field zeroed, as a freshly allocated account
+    /// decodes
+    fn blank_document() -> Document {
+        Document::deserialize(&mut &vec![0; Document::LEN][8..]).unwrap()
+    }
+    
+    /// Data of an ed25519 program instruction checking one signature by
+    /// `signer` over `message`, laid out as public key, signature, message
+    fn ed25519_instruction_data(signer: &Pubkey, message: &[u8]) -> Vec<u8> {
+        let (pubkey_offset, signature_offset, message_offset) = (16u16, 48u16, 112u16);
+        let mut data = vec![1, 0];
+        for field in [
+            signature_offset,
+            u16::MAX,
+            pubkey_offset,
+            u16::MAX,
+            message_offset,
+            message.len() as u16,
+            u16::MAX,
+        ] {
+            data.extend_from_slice(&field.to_le_bytes());
+        }
+        data.extend_from_slice(signer.as_ref());
+        data.extend_from_slice(&[0x5a; 64]);
+        data.extend_from_slice(message);
+        data
+    }
+    
+    /// Instructions sysvar data for a transaction with an ed25519 check of
+    /// `signer` over `message` followed by a call to this program
+    fn instructions_sysvar_data(signer: &Pubkey, message: &[u8]) -> Vec<u8> {
+        let ed25519_data = ed25519_instruction_data(signer, message);
+        sysvar::instructions::construct_instructions_data(&[
+            sysvar::instructions::BorrowedInstruction {
+                program_id: &ed25519_program::ID,
+                accounts: Vec::new(),
+                data: &ed25519_data,
+            },
+            sysvar::instructions::BorrowedInstruction {
+                program_id: &ID,
+                accounts: Vec::new(),
+                data: &[],
+            },
+        ])
+    }
+    
+    #[test]
+    fn validate_hash_accepts_a_lowercase_sha256_digest() {
+        let hash = "9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08";
+        assert_eq!(validate_hash(hash, HashAlgorithm::Sha256), Ok(()));
+    }
+    
+    #[test]
+    fn validate_hash_rejects_an_uppercase_digest() {
+        let hash = "9F86D081884C7D659A2FEAA0C55AD015A3BF4F1B2B0B822CD15D6C15B0F00A08";
+        assert_eq!(
+            validate_hash(hash, HashAlgorithm::Sha256),
+            Err(DocumentError::InvalidHashFormat.into())
+        );
+    }
+    
+    #[test]
+    fn validate_hash_rejects_a_short_digest() {
+        assert_eq!(
+            validate_hash("9f86d081884c7d65", HashAlgorithm::Sha256),
+            Err(DocumentError::InvalidHashFormat.into())
+        );
+        assert_eq!(
+            validate_hash("", HashAlgorithm::Sha256),
+            Err(DocumentError::InvalidHashFormat.into())
+        );
+    }
+    
+    #[test]
+    fn validate_hash_rejects_non_hex_characters() {
+        let hash = "zz86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08";
+        assert_eq!(
+            validate_hash(hash, HashAlgorithm::Sha256),
+            Err(DocumentError::InvalidHashFormat.into())
+        );
+    }
+    
+    #[test]
+    fn validate_hash_uses_the_algorithm_digest_length() {
+        let sha512 = "ab".repeat(64);
+        assert_eq!(validate_hash(&sha512, HashAlgorithm::Sha512), Ok(()));
+        assert_eq!(
+            validate_hash(&sha512, HashAlgorithm::Sha256),
+            Err(DocumentError::InvalidHashFormat.into())
+        );
+    }
+    
+    #[test]
+    fn document_expires_exactly_at_expires_at() {
+        let mut document = blank_document();
+        assert!(!document.is_expired(i64::MAX));
+        
+        document.expires_at = Some(1_000);
+        assert!(!document.is_expired(999));
+        assert!(document.is_expired(1_000));
+        assert!(document.is_expired(1_001));
+    }
+    
+    #[test]
+    fn expiry_only_moves_later() {
+        let mut document = blank_document();
+        assert_eq!(
+            document.extend_expiry(1_000),
+            Err(DocumentError::ExpiryNotExtended.into())
+        );
+        
+        document.expires_at = Some(1_000);
+        assert_eq!(
+            document.extend_expiry(1_000),
+            Err(DocumentError::ExpiryNotExtended.into())
+        );
+        assert_eq!(
+            document.extend_expiry(999),
+            Err(DocumentError::ExpiryNotExtended.into())
+        );
+        assert_eq!(document.extend_expiry(1_001), Ok(()));
+        assert_eq!(document.expires_at, Some(1_001));
+    }
+    
+    #[test]
+    fn validate_mime_type_accepts_allowed_types() {
+        for mime_type in ALLOWED_MIME_TYPES {
+            assert_eq!(validate_mime_type(mime_type), Ok(()));
+        }
+    }
+    
+    #[test]
+    fn validate_mime_type_rejects_other_types() {
+        for mime_type in ["application/x-msdownload", "APPLICATION/PDF", "image/png ", ""] {
+            assert_eq!(
+                validate_mime_type(mime_type),
+                Err(DocumentError::UnsupportedMimeType.into())
+            );
+        }
+    }
+    
+    #[test]
+    fn batch_entries_check_the_mime_type() {
+        let mut input = DocumentInput {
+            document_hash: "ab".repeat(32),
+            hash_algorithm: HashAlgorithm::Sha256,
+            document_name: "Lease".to_string(),
+            document_type: "contract".to_string(),
+            content_length: 1_024,
+            mime_type: "application/pdf".to_string(),
+            metadata: None,
+            expires_at: None,
+            required_signers: Vec::new(),
+            signature_threshold: 0,
+            signer_sequence: Vec::new(),
+            signing_deadline: None,
+            storage_uri: None,
+            category: None,
+            parent: None,
+        };
+        assert_eq!(input.validate(), Ok(()));
+        
+        input.mime_type = "text/html".to_string();
+        assert_eq!(input.validate(), Err(DocumentError::UnsupportedMimeType.into()));
+    }
+    
+    #[test]
+    fn authority_and_delegate_may_update() {
+        let (authority, delegate) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut document = blank_document();
+        document.authority = authority;
+        document.delegate = Some(delegate);
+        
+        assert_eq!(document.check_updater(&authority, None), Ok(()));
+        assert_eq!(document.check_updater(&delegate, None), Ok(()));
+    }
+    
+    #[test]
+    fn revoked_delegate_may_not_update() {
+        let (authority, delegate) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut document = blank_document();
+        document.authority = authority;
+        // `set_delegate(None)` clears the delegate
+        document.delegate = None;
+        
+        assert_eq!(
+            document.check_updater(&delegate, None),
+            Err(DocumentError::Unauthorized.into())
+        );
+        assert_eq!(
+            document.check_updater(&Pubkey::new_unique(), None),
+            Err(DocumentError::Unauthorized.into())
+        );
+    }
+    
+    #[test]
+    fn batch_entries_check_signing_rules_and_storage_uri() {
+        let signers = vec![Pubkey::new_unique(), Pubkey::new_unique()];
+        let mut input = DocumentInput {
+            document_hash: "ab".repeat(32),
+            hash_algorithm: HashAlgorithm::Sha256,
+            document_name: "Minutes".to_string(),
+            document_type: "record".to_string(),
+            content_length: 2_048,
+            mime_type: "application/pdf".to_string(),
+            metadata: None,
+            expires_at: Some(2_000_000_000),
+            required_signers: signers.clone(),
+            signature_threshold: 2,
+            signer_sequence: Vec::new(),
+            signing_deadline: None,
+            storage_uri: Some(format!("ipfs://{}", "Q".repeat(46))),
+            category: None,
+            parent: None,
+        };
+        assert_eq!(input.validate(), Ok(()));
+        
+        input.signature_threshold = 3;
+        assert_eq!(input.validate(), Err(DocumentError::InvalidThreshold.into()));
+        
+        input.signature_threshold = 0;
+        input.signer_sequence = signers;
+        assert_eq!(input.validate(), Err(DocumentError::InvalidSignerSequence.into()));
+        
+        input.signer_sequence = Vec::new();
+        input.storage_uri = Some("https://example.com/lease.pdf".to_string());
+        assert_eq!(input.validate(), Err(DocumentError::InvalidStorageUri.into()));
+    }
+    
+    #[test]
+    fn completion_threshold_follows_the_signing_rules() {
+        let mut document = blank_document();
+        assert_eq!(document.completion_threshold(), None);
+        
+        document.required_signers = vec![Pubkey::new_unique(); 3];
+        assert_eq!(document.completion_threshold(), Some(3));
+        
+        document.signature_threshold = 2;
+        assert_eq!(document.completion_threshold(), Some(2));
+        
+        let mut sequenced = blank_document();
+        sequenced.signer_sequence = vec![Pubkey::new_unique(); 4];
+        assert_eq!(sequenced.completion_threshold(), Some(4));
+    }
+    
+    #[test]
+    fn threshold_is_reached_at_and_over_the_threshold() {
+        let mut document = blank_document();
+        document.required_signers = vec![Pubkey::new_unique(); 3];
+        document.signature_threshold = 2;
+        
+        document.signatures_count = 1;
+        assert!(!document.threshold_reached(0));
+        document.signatures_count = 2;
+        assert!(document.threshold_reached(0));
+        document.signatures_count = 3;
+        assert!(document.threshold_reached(0));
+    }
+    
+    #[test]
+    fn zero_threshold_without_signers_never_completes() {
+        let mut document = blank_document();
+        document.signatures_count = 10;
+        assert!(!document.threshold_reached(0));
+    }
+    
+    #[test]
+    fn threshold_is_locked_after_the_first_signature() {
+        let mut document = blank_document();
+        document.required_signers = vec![Pubkey::new_unique(); 3];
+        assert_eq!(document.set_signature_threshold(2), Ok(()));
+        assert_eq!(
+            document.set_signature_threshold(4),
+            Err(DocumentError::InvalidThreshold.into())
+        );
+        
+        document.signatures_count = 1;
+        assert_eq!(
+            document.set_signature_threshold(3),
+            Err(DocumentError::ThresholdLocked.into())
+        );
+        assert_eq!(document.signature_threshold, 2);
+    }
+    
+    #[test]
+    fn ed25519_data_matches_the_signer_and_message() {
+        let signer = Pubkey::new_unique();
+        let hash = "ab".repeat(32);
+        let data = ed25519_instruction_data(&signer, hash.as_bytes());
+        
+        assert!(ed25519_data_matches(&data, &signer, hash.as_bytes()));
+        assert!(!ed25519_data_matches(&data, &Pubkey::new_unique(), hash.as_bytes()));
+        assert!(!ed25519_data_matches(&data, &signer, "cd".repeat(32).as_bytes()));
+        assert!(!ed25519_data_matches(&data, &signer, &hash.as_bytes()[..32]));
+    }
+    
+    #[test]
+    fn ed25519_data_must_be_inline_and_single() {
+        let signer = Pubkey::new_unique();
+        let message = b"document hash";
+        let data = ed25519_instruction_data(&signer, message);
+        
+        // Data read from another instruction could be anything
+        let mut elsewhere = data.clone();
+        elsewhere[8..10].copy_from_slice(&0u16.to_le_bytes());
+        assert!(!ed25519_data_matches(&elsewhere, &signer, message));
+        
+        let mut two_signatures = data.clone();
+        two_signatures[0] = 2;
+        assert!(!ed25519_data_matches(&two_signatures, &signer, message));
+        
+        // The signature must fit in the data
+        let truncated = &data[..100];
+        assert!(!ed25519_data_matches(truncated, &signer, message));
+        assert!(!ed25519_data_matches(&data[..10], &signer, message));
+        assert!(!ed25519_data_matches(&[], &signer, message));
+    }
+    
+    #[test]
+    fn withdrawals_leave_the_rent_exempt_minimum() {
+        let rent = Rent::default();
+        let minimum = rent.minimum_balance(0);
+        let key = Pubkey::new_unique();
+        let mut lamports = minimum + 5_000;
+        let mut data = [];
+        let treasury = AccountInfo::new(
+            &key,
+            false,
+            true,
+            &mut lamports,
+            &mut data,
+            &system_program::ID,
+            false,
+            0,
+        );
+        assert_eq!(withdrawable_lamports(&treasury, &rent), 5_000);
+        
+        **treasury.try_borrow_mut_lamports().unwrap() = minimum - 1;
+        assert_eq!(withdrawable_lamports(&treasury, &rent), 0);
+    }
+    
+    #[test]
+    fn validate_storage_uri_accepts_ipfs_and_arweave() {
+        let cid_v0 = "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG";
+        let cid_v1 = "bafybeigdyrzt5sfp7udm7hu76uh7y26nf3efuylqabf3oclgtqy55fbzdi";
+        assert_eq!(validate_storage_uri(&format!("ipfs://{cid_v0}")), Ok(()));
+        assert_eq!(validate_storage_uri(&format!("ipfs://{cid_v1}")), Ok(()));
+        assert_eq!(
+            validate_storage_uri("ar://bNbA3TEQVL60xlgCcqdz4ZPHFZ711cZ3hmkpGttDt_U"),
+            Ok(())
+        );
+    }
+    
+    #[test]
+    fn validate_storage_uri_rejects_other_uris() {
+        for uri in [
+            "https://example.com/lease.pdf",
+            "ipfs://QmTooShort",
+            "ipfs://QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPb/G",
+            "ar://bNbA3TEQVL60xlgCcqdz4ZPHFZ711cZ3hmkpGttDt_",
+            "ar://bNbA3TEQVL60xlgCcqdz4ZPHFZ711cZ3hmkpGttDt+U",
+            "IPFS://QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG",
+            "",
+        ] {
+            assert_eq!(
+                validate_storage_uri(uri),
+                Err(DocumentError::InvalidStorageUri.into()),
+                "{uri}"
+            );
+        }
+    }
+    
+    #[test]
+    fn verified_signing_finds_a_matching_ed25519_instruction() {
+        let signer = Pubkey::new_unique();
+        let hash = "ab".repeat(32);
+        let mut data = instructions_sysvar_data(&signer, hash.as_bytes());
+        let mut lamports = 0;
+        let instructions = AccountInfo::new(
+            &sysvar::instructions::ID,
+            false,
+            false,
+            &mut lamports,
+            &mut data,
+            &sysvar::ID,
+            false,
+            0,
+        );
+        
+        assert_eq!(
+            verify_ed25519_instruction(&instructions, &signer, hash.as_bytes()),
+            Ok(())
+        );
+    }
+    
+    #[test]
+    fn verified_signing_rejects_a_mismatched_message() {
+        let signer = Pubkey::new_unique();
+        let signed_hash = "ab".repeat(32);
+        let document_hash = "cd".repeat(32);
+        let mut data = instructions_sysvar_data(&signer, signed_hash.as_bytes());
+        let mut lamports = 0;
+        let instructions = AccountInfo::new(
+            &sysvar::instructions::ID,
+            false,
+            false,
+            &mut lamports,
+            &mut data,
+            &sysvar::ID,
+            false,
+            0,
+        );
+        
+        assert_eq!(
+            verify_ed25519_instruction(&instructions, &signer, document_hash.as_bytes()),
+            Err(DocumentError::InvalidSignature.into())
+        );
+        assert_eq!(
+            verify_ed25519_instruction(&instructions, &Pubkey::new_unique(), signed_hash.as_bytes()),
+            Err(DocumentError::InvalidSignature.into())
+        );
+    }
+    
+    /// Leaves, inner nodes and root of a 4-leaf tree built as `merkle_root`
+    /// expects
+    fn four_leaf_tree() -> ([[u8; 32]; 4], [[u8; 32]; 2], [u8; 32]) {
+        let leaves = [0u8, 1, 2, 3].map(|i| hash(&[b'd', b'o', b'c', i]).to_bytes());
+        let hashed = leaves.map(|leaf| hashv(&[&[0x00], &leaf]).to_bytes());
+        let nodes = [
+            hashv(&[&[0x01], &hashed[0], &hashed[1]]).to_bytes(),
+            hashv(&[&[0x01], &hashed[2], &hashed[3]]).to_bytes(),
+        ];
+        let root = hashv(&[&[0x01], &nodes[0], &nodes[1]]).to_bytes();
+        (leaves, nodes, root)
+    }
+    
+    #[test]
+    fn merkle_proofs_verify_every_leaf_of_a_four_leaf_tree() {
+        let (leaves, nodes, root) = four_leaf_tree();
+        let hashed = leaves.map(|leaf| hashv(&[&[0x00], &leaf]).to_bytes());
+        
+        assert_eq!(merkle_root(&leaves[0], 0, &[hashed[1], nodes[1]]), Some(root));
+        assert_eq!(merkle_root(&leaves[1], 1, &[hashed[0], nodes[1]]), Some(root));
+        assert_eq!(merkle_root(&leaves[2], 2, &[hashed[3], nodes[0]]), Some(root));
+        assert_eq!(merkle_root(&leaves[3], 3, &[hashed[2], nodes[0]]), Some(root));
+    }
+    
+    #[test]
+    fn merkle_proofs_reject_wrong_siblings_and_positions() {
+        let (leaves, nodes, root) = four_leaf_tree();
+        let hashed = leaves.map(|leaf| hashv(&[&[0x00], &leaf]).to_bytes());
+        
+        // Wrong sibling
+        assert_ne!(merkle_root(&leaves[0], 0, &[hashed[2], nodes[1]]), Some(root));
+        // Right proof, wrong position
+        assert_ne!(merkle_root(&leaves[0], 1, &[hashed[1], nodes[1]]), Some(root));
+        // Index beyond the depth of the proof
+        assert_eq!(merkle_root(&leaves[0], 4, &[hashed[1], nodes[1]]), None);
+    }
+    
+    #[test]
+    fn merkle_inner_nodes_do_not_pass_as_leaves() {
+        let (_, nodes, root) = four_leaf_tree();
+        
+        assert_ne!(merkle_root(&nodes[0], 0, &[nodes[1]]), Some(root));
+        assert_ne!(merkle_root(&root, 0, &[]), Some(root));
+    }
+    
+    #[test]
+    fn previous_owners_keep_only_the_most_recent() {
+        let mut document = blank_document();
+        let owners: Vec<Pubkey> = (0..MAX_PREVIOUS_OWNERS + 2).map(|_| Pubkey::new_unique()).collect();
+        let current = owners.len() - 1;
+        
+        document.authority = owners[0];
+        for owner in &owners[1..] {
+            document.transfer_to(*owner);
+            assert!(document.previous_owners.len() <= MAX_PREVIOUS_OWNERS);
+        }
+        
+        // The first owner dropped out; the rest are kept oldest first
+        assert_eq!(document.previous_owners, owners[1..current]);
+        assert_eq!(document.authority, owners[current]);
+    }
+    
+    #[test]
+    fn signer_lists_reject_the_entry_past_the_cap() {
+        let signers: Vec<Pubkey> = (0..=MAX_REQUIRED_SIGNERS).map(|_| Pubkey::new_unique()).collect();
+        let (full, over) = (&signers[..MAX_REQUIRED_SIGNERS], &signers[..]);
+        
+        assert!(validate_signing_rules(full, 1, &[]).is_ok());
+        assert_eq!(
+            validate_signing_rules(over, 1, &[]),
+            Err(DocumentError::TooManyRequiredSigners.into())
+        );
+        assert!(validate_signing_rules(&[], 0, full).is_ok());
+        assert_eq!(
+            validate_signing_rules(&[], 0, over),
+            Err(DocumentError::TooManyRequiredSigners.into())
+        );
+    }
+    
+    #[test]
+    fn metadata_rejects_the_tag_past_the_cap() {
+        let mut metadata = DocumentMetadata {
+            title: "Lease".to_string(),
+            author: "Landlord".to_string(),
+            tags: vec!["t".repeat(MAX_TAG_LEN); MAX_TAGS],
+            created_at: 0,
+        };
+        assert!(metadata.validate().is_ok());
+        
+        metadata.tags.push("extra".to_string());
+        assert_eq!(metadata.validate(), Err(DocumentError::TooManyTags.into()));
+    }
+    
+    #[test]
+    fn documents_at_every_cap_fit_their_allocation() {
+        let mut document = blank_document();
+        document.required_signers = vec![Pubkey::new_unique(); MAX_REQUIRED_SIGNERS];
+        document.signer_sequence = vec![Pubkey::new_unique(); MAX_REQUIRED_SIGNERS];
+        for _ in 0..MAX_PREVIOUS_OWNERS {
+            document.transfer_to(Pubkey::new_unique());
+        }
+        document.metadata = Some(DocumentMetadata {
+            title: "t".repeat(MAX_TITLE_LEN),
+            author: "a".repeat(MAX_AUTHOR_LEN),
+            tags: vec!["t".repeat(MAX_TAG_LEN); MAX_TAGS],
+            created_at: 0,
+        });
+        
+        assert!(8 + document.try_to_vec().unwrap().len() <= Document::LEN);
+    }
+    
+    #[test]
+    fn quota_counts_the_whole_registration() {
+        let mut manager = DocumentManager::deserialize(&mut &vec![0; DocumentManager::LEN][8..]).unwrap();
+        
+        // 0 means unlimited
+        assert!(manager.check_quota(u64::MAX, 1).is_ok());
+        
+        manager.max_documents_per_authority = 3;
+        assert!(manager.check_quota(0, 3).is_ok());
+        assert!(manager.check_quota(2, 1).is_ok());
+        assert_eq!(
+            manager.check_quota(3, 1),
+            Err(DocumentError::AuthorityQuotaExceeded.into())
+        );
+        // A batch is rejected as a whole when it would cross the quota
+        assert_eq!(
+            manager.check_quota(1, 3),
+            Err(DocumentError::AuthorityQuotaExceeded.into())
+        );
+        assert_eq!(
+            manager.check_quota(u64::MAX, 1),
+            Err(DocumentError::AuthorityQuotaExceeded.into())
+        );
+    }
+    
+    #[test]
+    fn sequence_admits_only_the_next_signer() {
+        let mut document = blank_document();
+        let signers = [Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique()];
+        document.signer_sequence = signers.to_vec();
+        
+        assert!(document.check_sequence(&signers[0]).is_ok());
+        // Skipping a position
+        assert_eq!(
+            document.check_sequence(&signers[1]),
+            Err(DocumentError::OutOfOrderSignature.into())
+        );
+        assert_eq!(
+            document.check_sequence(&Pubkey::new_unique()),
+            Err(DocumentError::SignerNotAuthorized.into())
+        );
+        
+        document.signatures_count = 1;
+        assert!(document.check_sequence(&signers[1]).is_ok());
+        // Signing twice
+        assert_eq!(
+            document.check_sequence(&signers[0]),
+            Err(DocumentError::OutOfOrderSignature.into())
+        );
+        
+        document.signatures_count = 3;
+        assert_eq!(
+            document.check_sequence(&signers[2]),
+            Err(DocumentError::OutOfOrderSignature.into())
+        );
+    }
+    
+    #[test]
+    fn documents_without_a_sequence_accept_any_order() {
+        let document = blank_document();
+        
+        assert!(document.check_sequence(&Pubkey::new_unique()).is_ok());
+    }
+    
+    #[test]
+    fn expired_approvals_do_not_count_toward_the_threshold() {
+        let now = 1_700_000_000;
+        let signers = [Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique()];
+        let mut document = blank_document();
+        document.required_signers = signers.to_vec();
+        document.signature_threshold = 2;
+        
+        document.record_approval(&signers[0], Some(now + 60)).unwrap();
+        document.record_approval(&signers[1], None).unwrap();
+        assert!(document.threshold_reached(now));
+        assert!(document.threshold_reached(now + 59));
+        // The first approval lapses, leaving one live approval of two
+        assert_eq!(document.live_approvals(now + 60), 1);
+        assert!(!document.threshold_reached(now + 60));
+        
+        // Pruning it changes nothing; a fresh approval completes again
+        document.release_approval(&signers[0], Some(now + 60)).unwrap();
+        assert!(!document.threshold_reached(now + 60));
+        document.record_approval(&signers[2], None).unwrap();
+        assert!(document.threshold_reached(now + 60));
+    }
+    
+    #[test]
+    fn pruning_mid_sequence_makes_that_signer_next() {
+        let signers = [Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique()];
+        let mut document = blank_document();
+        document.signer_sequence = signers.to_vec();
+        
+        document.record_approval(&signers[0], Some(100)).unwrap();
+        document.record_approval(&signers[1], None).unwrap();
+        assert_eq!(document.latest_sequence_position(), Some(1));
+        
+        document.release_approval(&signers[0], Some(100)).unwrap();
+        assert_eq!(document.sequence_signed(), 0b010);
+        assert_eq!(document.latest_sequence_position(), Some(1));
+        assert!(document.check_sequence(&signers[0]).is_ok());
+        assert_eq!(
+            document.check_sequence(&signers[2]),
+            Err(DocumentError::OutOfOrderSignature.into())
+        );
+        
+        document.record_approval(&signers[0], None).unwrap();
+        assert_eq!(document.sequence_signed(), 0b011);
+        assert!(document.check_sequence(&signers[2]).is_ok());
+        document.record_approval(&signers[2], None).unwrap();
+        assert!(document.threshold_reached(0));
+    }
+    
+    #[test]
+    fn releasing_the_latest_approval_leaves_no_gap() {
+        let signers = [Pubkey::new_unique(), Pubkey::new_unique()];
+        let mut document = blank_document();
+        document.signer_sequence = signers.to_vec();
+        
+        document.record_approval(&signers[0], None).unwrap();
+        document.record_approval(&signers[1], None).unwrap();
+        document.release_approval(&signers[1], None).unwrap();
+        
+        assert_eq!(document.sequence_gaps, 0);
+        assert_eq!(document.sequence_signed(), 0b01);
+        assert!(document.check_sequence(&signers[1]).is_ok());
+    }
+    
+    #[test]
+    fn released_approvals_decrement_the_count_down_to_zero() {
+        let signers = [Pubkey::new_unique(), Pubkey::new_unique()];
+        let mut document = blank_document();
+        
+        document.record_approval(&signers[0], None).unwrap();
+        document.record_approval(&signers[1], None).unwrap();
+        assert_eq!(document.signatures_count, 2);
+        
+        document.release_approval(&signers[1], None).unwrap();
+        assert_eq!(document.signatures_count, 1);
+        document.release_approval(&signers[0], None).unwrap();
+        assert_eq!(document.signatures_count, 0);
+        
+        assert_eq!(
+            document.release_approval(&signers[0], None),
+            Err(DocumentError::CounterUnderflow.into())
+        );
+        assert_eq!(document.signatures_count, 0);
+    }
+    
+    /// Runs `RevokeDocument`'s account validation with `data` stored at
+    /// `document_key` and `authority` signing
+    fn validate_revoke(document_key: Pubkey, mut data: Vec<u8>, authority: Pubkey) -> Result<()> {
+        let (mut document_lamports, mut authority_lamports) = (1_000_000, 1_000_000);
+        let mut no_data = [0u8; 0];
+        let system = anchor_lang::system_program::ID;
+        let accounts = [
+            AccountInfo::new(&document_key, false, true, &mut document_lamports, &mut data, &ID, false, 0),
+            AccountInfo::new(&authority, true, true, &mut authority_lamports, &mut no_data, &system, false, 0),
+        ];
+        RevokeDocument::try_accounts(
+            &ID,
+            &mut &accounts[..],
+            &[],
+            &mut std::collections::BTreeMap::new(),
+            &mut std::collections::BTreeSet::new(),
+        )
+        .map(|_| ())
+    }
+    
+    #[test]
+    fn documents_are_only_accepted_at_their_own_address() {
+        let (alice, mallory) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let document_address = |registrant: &Pubkey| {
+            Pubkey::find_program_address(&[b"document", registrant.as_ref(), &0u64.to_le_bytes()], &ID)
+        };
+        let account_data = |registrant: Pubkey, bump: u8| {
+            let mut document = blank_document();
+            document.authority = registrant;
+            document.registrant = registrant;
+            document.bump = bump;
+            let mut data = Document::DISCRIMINATOR.to_vec();
+            document.serialize(&mut data).unwrap();
+            data.resize(Document::LEN, 0);
+            data
+        };
+        let (alice_document, alice_bump) = document_address(&alice);
+        let (mallory_document, mallory_bump) = document_address(&mallory);
+        
+        assert!(validate_revoke(alice_document, account_data(alice, alice_bump), alice).is_ok());
+        // Mallory's document passed where it claims to be Alice's
+        assert_eq!(
+            validate_revoke(alice_document, account_data(mallory, mallory_bump), mallory),
+            Err(ErrorCode::ConstraintSeeds.into())
+        );
+        // Alice's document data at an address that is not hers
+        assert_eq!(
+            validate_revoke(mallory_document, account_data(alice, alice_bump), alice),
+            Err(ErrorCode::ConstraintSeeds.into())
+        );
+        // Legacy documents must backfill their bump first
+        assert_eq!(
+            validate_revoke(alice_document, account_data(alice, 0), alice),
+            Err(ErrorCode::ConstraintSeeds.into())
+        );
+    }
+    
+    #[test]
+    fn closing_an_archived_document_returns_its_rent() {
+        let alice = Pubkey::new_unique();
+        let mut ledger = ledger_with_manager(alice);
+        let document = register(&mut ledger, alice, registration("lease")).unwrap();
+        let hash_index = hash_index_address(&digest_hex("lease"));
+        
+        // Live documents are only closed with `force`
+        assert_eq!(
+            close(&mut ledger, document, alice, false),
+            Err(program_error(DocumentError::DocumentActive))
+        );
+        archive(&mut ledger, document, alice).unwrap();
+        
+        let rent = ledger.lamports(&document) + ledger.lamports(&hash_index);
+        let before = ledger.lamports(&alice);
+        close(&mut ledger, document, alice, false).unwrap();
+        
+        assert_eq!(ledger.lamports(&alice), before + rent);
+        assert_eq!(ledger.lamports(&document), 0);
+        assert!(!ledger.exists(&document));
+        assert!(!ledger.exists(&hash_index));
+    }
+    
+    #[test]
+    fn wallets_registering_together_each_start_at_index_zero() {
+        let alice = Pubkey::new_unique();
+        let bob = Pubkey::new_unique();
+        let mut ledger = ledger_with_manager(alice);
+        ledger.fund(bob, LAMPORTS_PER_SOL);
+        
+        // Bob cannot claim the address derived from alice's first index
+        let squatted = accounts::RegisterDocument {
+            document_manager: manager_address(),
+            authority_stats: stats_address(&bob),
+            document: document_address(&alice, 0),
+            hash_index: hash_index_address(&digest_hex("invoice")),
+            category: None,
+            parent: None,
+            treasury: treasury_address(),
+            payer_token_account: None,
+            treasury_token_account: None,
+            token_program: None,
+            allowlist_entry: None,
+            organization: None,
+            org_member: None,
+            collection: None,
+            authority: bob,
+            system_program: system_program::ID,
+        };
+        assert_eq!(
+            ledger.send(squatted, &[], registration("invoice")),
+            Err(program_error(ErrorCode::ConstraintSeeds))
+        );
+        
+        // Both registrations land in the same batch at each wallet's index 0
+        let from_alice = register(&mut ledger, alice, registration("lease")).unwrap();
+        let from_bob = register(&mut ledger, bob, registration("invoice")).unwrap();
+        assert_eq!(from_alice, document_address(&alice, 0));
+        assert_eq!(from_bob, document_address(&bob, 0));
+        assert_ne!(from_alice, from_bob);
+        
+        for (document, registrant) in [(from_alice, alice), (from_bob, bob)] {
+            let stored = ledger.load::<Document>(&document);
+            assert_eq!(stored.registrant, registrant);
+            assert_eq!(stored.index, 0);
+            assert_eq!(ledger.load::<AuthorityStats>(&stats_address(&registrant)).document_count, 1);
+        }
+        assert_eq!(ledger.load::<DocumentManager>(&manager_address()).document_count, 2);
+    }
+    
+    #[test]
+    fn pausing_blocks_registration_but_not_closing() {
+        let alice = Pubkey::new_unique();
+        let mut ledger = ledger_with_manager(alice);
+        let document = register(&mut ledger, alice, registration("lease")).unwrap();
+        archive(&mut ledger, document, alice).unwrap();
+        
+        let set_paused = |ledger: &mut TestLedger, paused: bool| {
+            let accounts = accounts::SetPaused {
+                document_manager: manager_address(),
+                authority: alice,
+            };
+            if paused {
+                ledger.send(accounts, &[], instruction::Pause {})
+            } else {
+                ledger.send(accounts, &[], instruction::Unpause {})
+            }
+        };
+        set_paused(&mut ledger, true).unwrap();
+        
+        assert_eq!(
+            register(&mut ledger, alice, registration("invoice")),
+            Err(program_error(DocumentError::ProgramPaused))
+        );
+        close(&mut ledger, document, alice, false).unwrap();
+        assert!(!ledger.exists(&document));
+        
+        set_paused(&mut ledger, false).unwrap();
+        register(&mut ledger, alice, registration("invoice")).unwrap();
+    }
+    
+    #[test]
+    fn verify_document_returns_a_borsh_verification_result() {
+        let alice = Pubkey::new_unique();
+        let mut ledger = ledger_with_manager(alice);
+        let document = register(&mut ledger, alice, registration("lease")).unwrap();
+        
+        for (content, matched) in [("lease", true), ("forged lease", false)] {
+            ledger
+                .send(
+                    accounts::VerifyDocument { document },
+                    &[],
+                    instruction::VerifyDocument {
+                        expected_hash: digest_hex(content),
+                    },
+                )
+                .unwrap();
+            let result = VerificationResult::try_from_slice(&ledger.return_data().unwrap()).unwrap();
+            assert_eq!(
+                result,
+                VerificationResult {
+                    matched,
+                    owner: alice,
+                    timestamp: START,
+                }
+            );
+        }
+    }
+    
+    #[test]
+    fn every_update_is_kept_at_its_own_version_address() {
+        let alice = Pubkey::new_unique();
+        let mut ledger = ledger_with_manager(alice);
+        let document = register(&mut ledger, alice, registration("draft")).unwrap();
+        
+        let revisions = ["first revision", "second revision", "final"];
+        for content in revisions {
+            update(&mut ledger, document, alice, content).unwrap();
+        }
+        
+        // Registration is version 1, so the updates recorded 2 through 4
+        for (version, content) in (2..).zip(revisions) {
+            let recorded = ledger.load::<DocumentVersion>(&version_address(&document, version));
+            assert_eq!(recorded.document, document);
+            assert_eq!(recorded.version, version);
+            assert_eq!(recorded.hash, digest_hex(content));
+            assert_eq!(recorded.updated_by, alice);
+        }
+        assert_eq!(ledger.load::<Document>(&document).version, 4);
+    }
+    
+    #[test]
+    fn version_records_chain_back_to_the_registered_hash() {
+        let alice = Pubkey::new_unique();
+        let mut ledger = ledger_with_manager(alice);
+        let document = register(&mut ledger, alice, registration("draft")).unwrap();
+        for content in ["first revision", "second revision", "final"] {
+            update(&mut ledger, document, alice, content).unwrap();
+        }
+        
+        // Walk back from the current hash through each version's predecessor
+        let stored = ledger.load::<Document>(&document);
+        let mut hash = stored.document_hash;
+        for version in (2..=stored.version).rev() {
+            let recorded = ledger.load::<DocumentVersion>(&version_address(&document, version));
+            assert_eq!(recorded.hash, hash);
+            hash = recorded.previous_hash;
+        }
+        assert_eq!(hash, digest_hex("draft"));
+    }
+    
+    #[test]
+    fn updates_advance_updated_at_but_keep_created_at() {
+        let alice = Pubkey::new_unique();
+        let mut ledger = ledger_with_manager(alice);
+        let document = register(&mut ledger, alice, registration("draft")).unwrap();
+        let registered = ledger.load::<Document>(&document);
+        assert_eq!((registered.created_at, registered.updated_at), (START, START));
+        
+        ledger.advance(3600);
+        update(&mut ledger, document, alice, "final").unwrap();
+        
+        let updated = ledger.load::<Document>(&document);
+        assert_eq!(updated.created_at, START);
+        assert_eq!(updated.updated_at, START + 3600);
+    }
+    
+    #[test]
+    fn legal_hold_blocks_updates_until_released() {
+        let (admin, alice) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut ledger = ledger_with_manager(admin);
+        ledger.fund(alice, LAMPORTS_PER_SOL);
+        let document = register(&mut ledger, alice, registration("draft")).unwrap();
+        let legal_hold = |authority: Pubkey| accounts::SetLegalHold {
+            document_manager: manager_address(),
+            document,
+            authority,
+        };
+        
+        ledger.send(legal_hold(admin), &[], instruction::PlaceLegalHold {}).unwrap();
+        assert_eq!(
+            ledger.load::<Document>(&document).require_mutable(),
+            Err(DocumentError::LegalHold.into())
+        );
+        assert_eq!(
+            update(&mut ledger, document, alice, "final"),
+            Err(program_error(DocumentError::LegalHold))
+        );
+        // The document authority cannot lift the hold
+        assert_eq!(
+            ledger.send(legal_hold(alice), &[], instruction::ReleaseLegalHold {}),
+            Err(program_error(DocumentError::Unauthorized))
+        );
+        
+        ledger.send(legal_hold(admin), &[], instruction::ReleaseLegalHold {}).unwrap();
+        assert!(ledger.load::<Document>(&document).require_mutable().is_ok());
+        update(&mut ledger, document, alice, "final").unwrap();
+        assert_eq!(ledger.load::<Document>(&document).document_hash, digest_hex("final"));
+    }
+    
+    #[test]
+    fn co_signers_approve_in_one_instruction() {
+        let alice = Pubkey::new_unique();
+        let mut ledger = ledger_with_manager(alice);
+        let document = register(&mut ledger, alice, registration("lease")).unwrap();
+        let signers = [Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique()];
+        
+        sign_multi(&mut ledger, document, alice, &signers).unwrap();
+        
+        assert_eq!(ledger.load::<Document>(&document).signatures_count, 3);
+        for signer in &signers {
+            let signature = ledger.load::<DocumentSignature>(&signature_address(&document, signer));
+            assert_eq!(signature.document, document);
+            assert_eq!(signature.signer, *signer);
+            assert!(signature.kind == SignatureKind::Approve);
+        }
+        assert_eq!(ledger.load::<DocumentManager>(&manager_address()).total_signatures, 3);
+    }
+    
+    #[test]
+    fn co_signing_rejects_too_many_and_repeated_signers() {
+        let alice = Pubkey::new_unique();
+        let mut ledger = ledger_with_manager(alice);
+        let document = register(&mut ledger, alice, registration("lease")).unwrap();
+        
+        let crowd: Vec<Pubkey> = (0..=MAX_CO_SIGNERS).map(|_| Pubkey::new_unique()).collect();
+        assert_eq!(
+            sign_multi(&mut ledger, document, alice, &crowd),
+            Err(program_error(DocumentError::TooManyCoSigners))
+        );
+        
+        let bob = Pubkey::new_unique();
+        assert_eq!(
+            sign_multi(&mut ledger, document, alice, &[bob, bob]),
+            Err(program_error(DocumentError::DuplicateSigner))
+        );
+        assert_eq!(ledger.load::<Document>(&document).signatures_count, 0);
+        assert!(!ledger.exists(&signature_address(&document, &bob)));
+    }
+    
+    #[test]
+    fn closing_a_document_frees_a_quota_slot() {
+        let alice = Pubkey::new_unique();
+        let mut ledger = ledger_with_manager(alice);
+        ledger
+            .send(
+                accounts::SetAuthorityQuota {
+                    document_manager: manager_address(),
+                    authority: alice,
+                },
+                &[],
+                instruction::SetAuthorityQuota {
+                    max_documents_per_authority: 2,
+                },
+            )
+            .unwrap();
+        
+        let first = register(&mut ledger, alice, registration("lease")).unwrap();
+        register(&mut ledger, alice, registration("invoice")).unwrap();
+        assert_eq!(
+            register(&mut ledger, alice, registration("receipt")),
+            Err(program_error(DocumentError::AuthorityQuotaExceeded))
+        );
+        
+        archive(&mut ledger, first, alice).unwrap();
+        close(&mut ledger, first, alice, false).unwrap();
+        let stats = ledger.load::<AuthorityStats>(&stats_address(&alice));
+        assert_eq!((stats.document_count, stats.documents_owned), (2, 1));
+        
+        // The next document takes the next index, not the closed one
+        let third = register(&mut ledger, alice, registration("receipt")).unwrap();
+        assert_eq!(third, document_address(&alice, 2));
+    }
+    
+    #[test]
+    fn manager_tracks_archives_signatures_and_activity() {
+        let (alice, bob) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut ledger = ledger_with_manager(alice);
+        ledger.fund(bob, LAMPORTS_PER_SOL);
+        
+        let document = register(&mut ledger, alice, registration("lease")).unwrap();
+        let manager = ledger.load::<DocumentManager>(&manager_address());
+        assert_eq!(manager.last_activity_ts, START);
+        
+        ledger.advance(60);
+        sign(&mut ledger, document, bob).unwrap();
+        let manager = ledger.load::<DocumentManager>(&manager_address());
+        assert_eq!(manager.total_signatures, 1);
+        assert_eq!(manager.archived_count, 0);
+        assert_eq!(manager.last_activity_ts, START + 60);
+        
+        ledger.advance(60);
+        archive(&mut ledger, document, alice).unwrap();
+        let manager = ledger.load::<DocumentManager>(&manager_address());
+        assert_eq!(manager.document_count, 1);
+        assert_eq!(manager.total_signatures, 1);
+        assert_eq!(manager.archived_count, 1);
+        assert_eq!(manager.last_activity_ts, START + 120);
+    }
+    
+    #[test]
+    fn manager_stats_report_registrations_archives_and_signatures() {
+        let (alice, bob) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut ledger = ledger_with_manager(alice);
+        ledger.fund(bob, LAMPORTS_PER_SOL);
+        
+        let archived = register(&mut ledger, alice, registration("lease")).unwrap();
+        let signed = register(&mut ledger, alice, registration("invoice")).unwrap();
+        archive(&mut ledger, archived, alice).unwrap();
+        ledger.advance(30);
+        sign(&mut ledger, signed, bob).unwrap();
+        
+        ledger
+            .send(
+                accounts::GetManagerStats {
+                    document_manager: manager_address(),
+                },
+                &[],
+                instruction::GetManagerStats {},
+            )
+            .unwrap();
+        let stats = ManagerStats::try_from_slice(&ledger.return_data().unwrap()).unwrap();
+        assert_eq!(stats.authority, alice);
+        assert!(!stats.paused);
+        assert_eq!(stats.document_count, 2);
+        assert_eq!(stats.archived_count, 1);
+        assert_eq!(stats.total_signatures, 1);
+        assert_eq!(stats.last_activity_ts, START + 30);
+    }
+    
+    #[test]
+    fn tags_count_follows_added_and_removed_tags() {
+        let alice = Pubkey::new_unique();
+        let mut ledger = ledger_with_manager(alice);
+        let document = register(&mut ledger, alice, registration("lease")).unwrap();
+        let tag_address = |tag: &str| {
+            Pubkey::find_program_address(&[b"tag", hash(tag.as_bytes()).as_ref(), document.as_ref()], &ID).0
+        };
+        let add_tag = |ledger: &mut TestLedger, tag: String| {
+            ledger.send(
+                accounts::AddTag {
+                    document,
+                    document_tag: tag_address(&tag),
+                    authority: alice,
+                    system_program: system_program::ID,
+                },
+                &[],
+                instruction::AddTag { tag },
+            )
+        };
+        let tags_count = |ledger: &mut TestLedger| ledger.load::<Document>(&document).tags_count;
+        assert_eq!(tags_count(&mut ledger), 0);
+        
+        add_tag(&mut ledger, "lease".to_string()).unwrap();
+        assert_eq!(tags_count(&mut ledger), 1);
+        add_tag(&mut ledger, "2024".to_string()).unwrap();
+        assert_eq!(tags_count(&mut ledger), 2);
+        
+        ledger
+            .send(
+                accounts::RemoveTag {
+                    document,
+                    document_tag: tag_address("2024"),
+                    authority: alice,
+                },
+                &[],
+                instruction::RemoveTag {},
+            )
+            .unwrap();
+        assert_eq!(tags_count(&mut ledger), 1);
+        assert!(!ledger.exists(&tag_address("2024")));
+        
+        for i in 1..MAX_DOCUMENT_TAGS {
+            add_tag(&mut ledger, format!("tag-{i}")).unwrap();
+        }
+        assert_eq!(tags_count(&mut ledger), MAX_DOCUMENT_TAGS);
+        assert_eq!(
+            add_tag(&mut ledger, "one too many".to_string()),
+            Err(program_error(DocumentError::TooManyDocumentTags))
+        );
+        assert_eq!(tags_count(&mut ledger), MAX_DOCUMENT_TAGS);
+    }
+    
+    #[test]
+    fn appended_signature_leaves_verify_against_the_tree_root() {
+        let alice = Pubkey::new_unique();
+        let mut ledger = ledger_with_manager(alice);
+        for program in [spl_account_compression::ID, Noop::id()] {
+            ledger.insert(program, 1, bpf_loader::ID, &[], true);
+        }
+        let document = register(&mut ledger, alice, registration("lease")).unwrap();
+        let merkle_tree = Pubkey::new_unique();
+        ledger.insert(merkle_tree, 1, spl_account_compression::ID, &[], false);
+        let tree_authority =
+            Pubkey::find_program_address(&[b"tree-authority", document.as_ref()], &ID).0;
+        
+        ledger
+            .send(
+                accounts::InitSignatureTree {
+                    document,
+                    merkle_tree,
+                    tree_authority,
+                    authority: alice,
+                    compression_program: spl_account_compression::ID,
+                    noop_program: Noop::id(),
+                },
+                &[],
+                instruction::InitSignatureTree {
+                    max_depth: 3,
+                    max_buffer_size: 8,
+                },
+            )
+            .unwrap();
+        
+        let signers = [Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique()];
+        let mut leaves = Vec::new();
+        for (i, signer) in signers.iter().enumerate() {
+            ledger.advance(10);
+            let signature_hash = format!("signature-{i}");
+            ledger
+                .send(
+                    accounts::AppendSignatureLeaf {
+                        document_manager: manager_address(),
+                        document,
+                        merkle_tree,
+                        tree_authority,
+                        signer: *signer,
+                        compression_program: spl_account_compression::ID,
+                        noop_program: Noop::id(),
+                    },
+                    &[],
+                    instruction::AppendSignatureLeaf {
+                        signature_hash: signature_hash.clone(),
+                    },
+                )
+                .unwrap();
+            let timestamp = NOW.with(Cell::get);
+            leaves.push((*signer, signature_hash, timestamp));
+        }
+        assert_eq!(ledger.load::<Document>(&document).compressed_signatures_count, 3);
+        
+        let hashed: Vec<[u8; 32]> = leaves
+            .iter()
+            .map(|(signer, signature_hash, timestamp)| {
+                compressed_signature_leaf(&document, signer, signature_hash, *timestamp)
+            })
+            .collect();
+        let levels = compression_levels(&hashed, 3);
+        let root = levels.last().unwrap()[0];
+        let verify = |ledger: &mut TestLedger, leaf: usize, index: u32, timestamp: i64| {
+            let proof: Vec<AccountMeta> = compression_proof(&levels, leaf)
+                .into_iter()
+                .map(|node| AccountMeta::new_readonly(Pubkey::new_from_array(node), false))
+                .collect();
+            let (signer, signature_hash, _) = leaves[leaf].clone();
+            ledger.send(
+                accounts::VerifySignatureLeaf {
+                    document,
+                    merkle_tree,
+                    compression_program: spl_account_compression::ID,
+                },
+                &proof,
+                instruction::VerifySignatureLeaf {
+                    root,
+                    signer,
+                    signature_hash,
+                    timestamp,
+                    index,
+                },
+            )
+        };
+        
+        for (i, (_, _, timestamp)) in leaves.clone().into_iter().enumerate() {
+            verify(&mut ledger, i, i as u32, timestamp).unwrap();
+        }
+        // A leaf does not verify at another position or with another time
+        assert!(verify(&mut ledger, 1, 2, leaves[1].2).is_err());
+        assert!(verify(&mut ledger, 1, 1, leaves[1].2 + 1).is_err());
+    }
+}
@@ -0,0 +1,1468 @@
+//! Native document verification program
+//!
+//! This program provides document verification functionality on the Solana blockchain.
+//! It allows users to:
+//! 1. Register document hashes for verification
+//! 2. Verify document ownership
+//! 3. Transfer document ownership
+//! 4. Store metadata for documents
+
+use blokdoc_state::{is_lowercase_hex, VerificationResult, MAX_HASH_LEN};
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    hash::hash,
+    msg,
+    program::{invoke, invoke_signed, set_return_data},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    system_instruction,
+    sysvar::{rent::Rent, Sysvar},
+};
+
+/// Maximum length in bytes of a document's metadata.
+pub const MAX_METADATA_LEN: usize = 512;
+
+// Program entrypoint
+#[cfg(not(feature = "no-entrypoint"))]
+solana_program::entrypoint!(process_instruction);
+
+/// Instructions supported by the Document Verification program
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub enum DocumentInstruction {
+    /// Register a new document hash
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer, writable]` Owner account (pays for the document account)
+    /// 1. `[writable]` Document account, at `document_address(owner,
+    ///    document_hash)` (to be created)
+    /// 2. `[]` System program
+    RegisterDocument {
+        /// Document hash (SHA-256 hash of document content)
+        document_hash: String,
+        /// Optional metadata (JSON string)
+        metadata: Option<String>,
+    },
+
+    /// Update metadata for an existing document, resizing its account
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer, writable]` Owner account (pays for growth, receives refunds)
+    /// 1. `[writable]` Document account
+    /// 2. `[]` System program
+    UpdateMetadata {
+        /// New metadata (JSON string)
+        metadata: String,
+    },
+
+    /// Transfer document ownership in one step, with both owners signing.
+    /// Cancels any pending transfer.
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer]` Current owner account
+    /// 1. `[writable]` Document account
+    /// 2. `[signer]` New owner account, which must not be the zero pubkey
+    TransferOwnership,
+
+    /// Close a document and reclaim its rent
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer, writable]` Owner account (receives the reclaimed lamports)
+    /// 1. `[writable]` Document account
+    CloseDocument,
+
+    /// Propose a new owner for a document
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer]` Current owner account
+    /// 1. `[writable]` Document account
+    InitiateTransfer {
+        /// Owner that must accept the transfer
+        new_owner: Pubkey,
+    },
+
+    /// Accept a pending ownership transfer
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer]` Proposed owner account
+    /// 1. `[writable]` Document account
+    AcceptTransfer,
+
+    /// Cancel a pending ownership transfer
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer]` Current owner account
+    /// 1. `[writable]` Document account
+    CancelTransfer,
+
+    /// Compare a document's stored hash with an expected one, returning a
+    /// Borsh-encoded `VerificationResult` as program return data
+    /// 
+    /// Accounts expected:
+    /// 0. `[]` Document account
+    VerifyDocument {
+        /// Hash the caller expects the document to have
+        expected_hash: String,
+    },
+}
+
+/// Document account data
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct Document {
+    /// Owner public key
+    pub owner: Pubkey,
+    /// Document hash
+    pub document_hash: String,
+    /// Timestamp when document was registered
+    pub timestamp: u64,
+    /// Optional metadata
+    pub metadata: Option<String>,
+    /// Owner proposed by a pending ownership transfer
+    pub proposed_owner: Option<Pubkey>,
+}
+
+/// Address of the document `owner` registers with `document_hash`: the PDA
+/// of `["document", owner, sha256(document_hash)]`. Seeds are limited to 32
+/// bytes, so the hash is hashed again rather than truncated, which would let
+/// hashes sharing a prefix collide.
+pub fn document_address(program_id: &Pubkey, owner: &Pubkey, document_hash: &str) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"document", owner.as_ref(), hash(document_hash.as_bytes()).as_ref()],
+        program_id,
+    )
+}
+
+impl Document {
+    /// Reads the document stored in `account`. Fails with
+    /// `DocumentNotInitialized` if the account holds no document and with
+    /// `InvalidDocumentData` if its data does not decode.
+    pub fn load(account: &AccountInfo) -> Result<Self, ProgramError> {
+        let data = account.data.borrow();
+        if data.is_empty() {
+            return Err(DocumentVerificationError::DocumentNotInitialized.into());
+        }
+
+        let document = Self::deserialize(&mut &data[..])
+            .map_err(|_| DocumentVerificationError::InvalidDocumentData)?;
+
+        // Unregistered and closed accounts are zero-filled
+        if document.owner == Pubkey::default() {
+            return Err(DocumentVerificationError::DocumentNotInitialized.into());
+        }
+
+        Ok(document)
+    }
+}
+
+/// Errors returned by the native program, as `ProgramError::Custom` codes
+#[derive(thiserror::Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DocumentVerificationError {
+    /// Signer is not the document owner
+    #[error("Signer is not the document owner")]
+    NotDocumentOwner,
+    /// Document account is not the expected PDA
+    #[error("Document account does not match the expected address")]
+    DocumentAccountMismatch,
+    /// Metadata exceeds MAX_METADATA_LEN
+    #[error("Metadata exceeds the maximum size")]
+    MetadataTooLarge,
+    /// Document hash is empty, too long or not lowercase hex
+    #[error("Document hash is invalid")]
+    HashInvalid,
+    /// Document account already exists
+    #[error("Document account is already initialized")]
+    AccountAlreadyInitialized,
+    /// Document account was not passed as writable
+    #[error("Document account is not writable")]
+    DocumentAccountNotWritable,
+    /// Document account data could not be deserialized (code 6)
+    #[error("Document account data is invalid")]
+    InvalidDocumentData,
+    /// Proposed new owner is already the owner
+    #[error("Document is already owned by the proposed owner")]
+    SelfTransfer,
+    /// Signer is not the proposed owner of a pending transfer
+    #[error("Signer is not the proposed owner")]
+    NotProposedOwner,
+    /// No ownership transfer is pending
+    #[error("No ownership transfer is pending")]
+    NoPendingTransfer,
+    /// Lamport arithmetic overflowed
+    #[error("Lamport balance overflow")]
+    LamportsOverflow,
+    /// Document account is empty or zero-filled (code 11)
+    #[error("Document account is not initialized")]
+    DocumentNotInitialized,
+}
+
+impl From<DocumentVerificationError> for ProgramError {
+    fn from(e: DocumentVerificationError) -> Self {
+        ProgramError::Custom(e as u32)
+    }
+}
+
+/// Process program instruction
+pub fn process_instruction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    // Deserialize instruction
+    let instruction = DocumentInstruction::try_from_slice(instruction_data)
+        .map_err(|_| ProgramError::InvalidInstructionData)?;
+
+    match instruction {
+        DocumentInstruction::RegisterDocument { document_hash, metadata } => {
+            process_register_document(program_id, accounts, document_hash, metadata)
+        }
+        DocumentInstruction::UpdateMetadata { metadata } => {
+            process_update_metadata(program_id, accounts, metadata)
+        }
+        DocumentInstruction::TransferOwnership => {
+            process_transfer_ownership(program_id, accounts)
+        }
+        DocumentInstruction::CloseDocument => {
+            process_close_document(program_id, accounts)
+        }
+        DocumentInstruction::InitiateTransfer { new_owner } => {
+            process_initiate_transfer(program_id, accounts, new_owner)
+        }
+        DocumentInstruction::AcceptTransfer => {
+            process_accept_transfer(program_id, accounts)
+        }
+        DocumentInstruction::CancelTransfer => {
+            process_cancel_transfer(program_id, accounts)
+        }
+        DocumentInstruction::VerifyDocument { expected_hash } => {
+            process_verify_document(program_id, accounts, expected_hash)
+        }
+    }
+}
+
+/// Process RegisterDocument instruction
+fn process_register_document(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    document_hash: String,
+    metadata: Option<String>,
+) -> ProgramResult {
+    // Get account iterator
+    let account_info_iter = &mut accounts.iter();
+    
+    // Extract accounts
+    let owner_account = next_account_info(account_info_iter)?;
+    let document_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    // Verify owner is signer
+    if !owner_account.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // Verify the document account can be written
+    if !document_account.is_writable {
+        return Err(DocumentVerificationError::DocumentAccountNotWritable.into());
+    }
+
+    // Verify the system program is the real one before invoking it
+    if *system_program.key != solana_program::system_program::ID {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    // Verify the hash is non-empty lowercase hex of a bounded length
+    if document_hash.is_empty()
+        || document_hash.len() > MAX_HASH_LEN
+        || !is_lowercase_hex(&document_hash)
+    {
+        return Err(DocumentVerificationError::HashInvalid.into());
+    }
+
+    // Verify metadata fits its reserved size
+    if metadata.as_ref().is_some_and(|metadata| metadata.len() > MAX_METADATA_LEN) {
+        return Err(DocumentVerificationError::MetadataTooLarge.into());
+    }
+
+    // Verify the document account is the PDA for this owner and hash
+    let (expected_address, bump) = document_address(program_id, owner_account.key, &document_hash);
+    if expected_address != *document_account.key {
+        return Err(DocumentVerificationError::DocumentAccountMismatch.into());
+    }
+
+    // Verify the document has not been registered yet. Lamports alone do
+    // not count: anyone can fund the address before it is registered.
+    if !document_account.data_is_empty()
+        || *document_account.owner != solana_program::system_program::ID
+    {
+        return Err(DocumentVerificationError::AccountAlreadyInitialized.into());
+    }
+
+    // Create document data
+    let hash_seed = hash(document_hash.as_bytes());
+    let document = Document {
+        owner: *owner_account.key,
+        document_hash,
+        timestamp: solana_program::clock::Clock::get()?.unix_timestamp as u64,
+        metadata,
+        proposed_owner: None,
+    };
+
+    // Size the account from the serialized document, reserving room for a
+    // proposed owner during an ownership transfer
+    let document_data = document.try_to_vec()?;
+    let space = document_data.len() + 32;
+    let rent = Rent::get()?;
+    let lamports = rent.minimum_balance(space);
+
+    let signer_seeds: &[&[u8]] = &[
+        b"document",
+        owner_account.key.as_ref(),
+        hash_seed.as_ref(),
+        &[bump],
+    ];
+
+    if document_account.lamports() == 0 {
+        // Create document account owned by this program
+        msg!("Creating document account...");
+        invoke_signed(
+            &system_instruction::create_account(
+                owner_account.key,
+                document_account.key,
+                lamports,
+                space as u64,
+                program_id,
+            ),
+            &[
+                owner_account.clone(),
+                document_account.clone(),
+                system_program.clone(),
+            ],
+            &[signer_seeds],
+        )?;
+    } else {
+        // CreateAccount refuses a funded address, so top up its rent and
+        // allocate and assign it in separate steps
+        msg!("Taking over pre-funded document account...");
+        let shortfall = lamports.saturating_sub(document_account.lamports());
+        if shortfall > 0 {
+            invoke(
+                &system_instruction::transfer(owner_account.key, document_account.key, shortfall),
+                &[
+                    owner_account.clone(),
+                    document_account.clone(),
+                    system_program.clone(),
+                ],
+            )?;
+        }
+        invoke_signed(
+            &system_instruction::allocate(document_account.key, space as u64),
+            &[document_account.clone(), system_program.clone()],
+            &[signer_seeds],
+        )?;
+        invoke_signed(
+            &system_instruction::assign(document_account.key, program_id),
+            &[document_account.clone(), system_program.clone()],
+            &[signer_seeds],
+        )?;
+    }
+
+    // Verify the new account belongs to this program
+    if document_account.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    // Verify the new account cannot be purged for unpaid rent
+    if !rent.is_exempt(document_account.lamports(), document_account.data_len()) {
+        return Err(ProgramError::AccountNotRentExempt);
+    }
+
+    // Save document data to account
+    document_account.data.borrow_mut()[..document_data.len()].copy_from_slice(&document_data);
+
+    msg!("Document registered successfully");
+    Ok(())
+}
+
+/// Process UpdateMetadata instruction
+fn process_update_metadata(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    metadata: String,
+) -> ProgramResult {
+    // Get account iterator
+    let account_info_iter = &mut accounts.iter();
+    
+    // Extract accounts
+    let owner_account = next_account_info(account_info_iter)?;
+    let document_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    // Verify owner is signer
+    if !owner_account.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // Verify metadata fits its reserved size
+    if metadata.len() > MAX_METADATA_LEN {
+        return Err(DocumentVerificationError::MetadataTooLarge.into());
+    }
+
+    // Verify document account is owned by program
+    if document_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    // Deserialize document data
+    let mut document = Document::load(document_account)?;
+
+    // Verify ownership
+    if document.owner != *owner_account.key {
+        return Err(DocumentVerificationError::NotDocumentOwner.into());
+    }
+
+    // Update metadata
+    document.metadata = Some(metadata);
+
+    // Size the account to the new data, keeping the same reserve for a
+    // proposed owner as at registration. MAX_METADATA_LEN keeps growth well
+    // within the per-instruction realloc limit.
+    let document_data = document.try_to_vec()?;
+    let space = document_data.len() + 32;
+    let required_lamports = Rent::get()?.minimum_balance(space);
+    let current_lamports = document_account.lamports();
+
+    // Top up rent from the owner before growing
+    if required_lamports > current_lamports {
+        invoke(
+            &system_instruction::transfer(
+                owner_account.key,
+                document_account.key,
+                required_lamports - current_lamports,
+            ),
+            &[
+                owner_account.clone(),
+                document_account.clone(),
+                system_program.clone(),
+            ],
+        )?;
+    }
+
+    document_account.realloc(space, true)?;
+
+    // Refund rent no longer needed after shrinking
+    if current_lamports > required_lamports {
+        let refund = current_lamports - required_lamports;
+        **document_account.try_borrow_mut_lamports()? -= refund;
+        **owner_account.try_borrow_mut_lamports()? += refund;
+    }
+
+    // Save updated document data
+    document_account.data.borrow_mut()[..document_data.len()].copy_from_slice(&document_data);
+
+    msg!("Document metadata updated successfully");
+    Ok(())
+}
+
+/// Process TransferOwnership instruction
+fn process_transfer_ownership(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    // Get account iterator
+    let account_info_iter = &mut accounts.iter();
+    
+    // Extract accounts
+    let current_owner = next_account_info(account_info_iter)?;
+    let document_account = next_account_info(account_info_iter)?;
+    let new_owner = next_account_info(account_info_iter)?;
+
+    // Verify current owner is signer
+    if !current_owner.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // Reject the zero pubkey, which would lock the document forever
+    if *new_owner.key == Pubkey::default() {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    // Verify the new owner consents, as AcceptTransfer would require
+    if !new_owner.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // Verify document account is owned by program
+    if document_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    // Deserialize document data
+    let mut document = Document::load(document_account)?;
+
+    // Verify ownership
+    if document.owner != *current_owner.key {
+        return Err(DocumentVerificationError::NotDocumentOwner.into());
+    }
+
+    // Update owner, dropping any pending two-step transfer so the old
+    // proposal cannot be accepted against the new owner
+    document.owner = *new_owner.key;
+    document.proposed_owner = None;
+
+    // Serialize and save updated document data
+    document.serialize(&mut &mut document_account.data.borrow_mut()[..])?;
+
+    msg!("Document ownership transferred successfully");
+    Ok(())
+}
+
+/// Process CloseDocument instruction
+fn process_close_document(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    // Get account iterator
+    let account_info_iter = &mut accounts.iter();
+    
+    // Extract accounts
+    let owner_account = next_account_info(account_info_iter)?;
+    let document_account = next_account_info(account_info_iter)?;
+
+    // Verify owner is signer
+    if !owner_account.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // Verify document account is owned by program
+    if document_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    // Deserialize document data
+    let document = Document::load(document_account)?;
+
+    // Verify ownership
+    if document.owner != *owner_account.key {
+        return Err(DocumentVerificationError::NotDocumentOwner.into());
+    }
+
+    // Clear document data first, so the account cannot be revived with its
+    // old contents by refunding it later in the same transaction
+    document_account.data.borrow_mut().fill(0);
+    document_account.realloc(0, false)?;
+
+    // Hand the account back to the system program
+    document_account.assign(&solana_program::system_program::ID);
+
+    // Return all lamports to the owner
+    let lamports = document_account.lamports();
+    **owner_account.lamports.borrow_mut() = owner_account
+        .lamports()
+        .checked_add(lamports)
+        .ok_or(DocumentVerificationError::LamportsOverflow)?;
+    **document_account.lamports.borrow_mut() = 0;
+
+    msg!("Document closed successfully");
+    Ok(())
+}
+
+/// Process InitiateTransfer instruction
+fn process_initiate_transfer(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    new_owner: Pubkey,
+) -> ProgramResult {
+    // Get account iterator
+    let account_info_iter = &mut accounts.iter();
+    
+    // Extract accounts
+    let current_owner = next_account_info(account_info_iter)?;
+    let document_account = next_account_info(account_info_iter)?;
+
+    // Verify current owner is signer
+    if !current_owner.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // Verify document account is owned by program
+    if document_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    // Deserialize document data
+    let mut document = Document::load(document_account)?;
+
+    // Verify ownership
+    if document.owner != *current_owner.key {
+        return Err(DocumentVerificationError::NotDocumentOwner.into());
+    }
+
+    // Reject transfers to the current owner
+    if new_owner == document.owner {
+        return Err(DocumentVerificationError::SelfTransfer.into());
+    }
+
+    // Record the proposed owner
+    document.proposed_owner = Some(new_owner);
+
+    // Serialize and save updated document data
+    document.serialize(&mut &mut document_account.data.borrow_mut()[..])?;
+
+    msg!(
+        "TransferInitiated: document {} from {} to {}",
+        document_account.key,
+        document.owner,
+        new_owner
+    );
+    Ok(())
+}
+
+/// Process AcceptTransfer instruction
+fn process_accept_transfer(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    // Get account iterator
+    let account_info_iter = &mut accounts.iter();
+    
+    // Extract accounts
+    let new_owner = next_account_info(account_info_iter)?;
+    let document_account = next_account_info(account_info_iter)?;
+
+    // Verify proposed owner is signer
+    if !new_owner.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // Verify document account is owned by program
+    if document_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    // Deserialize document data
+    let mut document = Document::load(document_account)?;
+
+    // Verify the signer is the proposed owner
+    if document.proposed_owner != Some(*new_owner.key) {
+        return Err(DocumentVerificationError::NotProposedOwner.into());
+    }
+
+    // Move ownership and clear the proposal
+    let previous_owner = document.owner;
+    document.owner = *new_owner.key;
+    document.proposed_owner = None;
+
+    // Serialize and save updated document data
+    document.serialize(&mut &mut document_account.data.borrow_mut()[..])?;
+
+    msg!(
+        "TransferAccepted: document {} from {} to {}",
+        document_account.key,
+        previous_owner,
+        document.owner
+    );
+    Ok(())
+}
+
+/// Process CancelTransfer instruction
+fn process_cancel_transfer(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    // Get account iterator
+    let account_info_iter = &mut accounts.iter();
+    
+    // Extract accounts
+    let current_owner = next_account_info(account_info_iter)?;
+    let document_account = next_account_info(account_info_iter)?;
+
+    // Verify current owner is signer
+    if !current_owner.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // Verify document account is owned by program
+    if document_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    // Deserialize document data
+    let mut document = Document::load(document_account)?;
+
+    // Verify ownership
+    if document.owner != *current_owner.key {
+        return Err(DocumentVerificationError::NotDocumentOwner.into());
+    }
+
+    // Verify a transfer is pending
+    if document.proposed_owner.is_none() {
+        return Err(DocumentVerificationError::NoPendingTransfer.into());
+    }
+
+    // Clear the proposal
+    document.proposed_owner = None;
+
+    // Serialize and save updated document data
+    document.serialize(&mut &mut document_account.data.borrow_mut()[..])?;
+
+    msg!("Document ownership transfer cancelled");
+    Ok(())
+}
+
+/// Process VerifyDocument instruction
+fn process_verify_document(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    expected_hash: String,
+) -> ProgramResult {
+    // Get account iterator
+    let account_info_iter = &mut accounts.iter();
+    
+    // Extract accounts
+    let document_account = next_account_info(account_info_iter)?;
+
+    // Verify document account is owned by program
+    if document_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    // Deserialize document data
+    let document = Document::load(document_account)?;
+
+    // Return the comparison to the caller
+    let result = VerificationResult {
+        matched: document.document_hash == expected_hash,
+        owner: document.owner,
+        timestamp: document.timestamp as i64,
+    };
+    set_return_data(&result.try_to_vec()?);
+
+    msg!("Document verification: matched = {}", result.matched);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_program::{
+        clock::Clock,
+        entrypoint::MAX_PERMITTED_DATA_INCREASE,
+        instruction::Instruction,
+        program_stubs::{set_syscall_stubs, SyscallStubs},
+        program_utils::limited_deserialize,
+        system_instruction::SystemInstruction,
+        system_program,
+    };
+    use std::sync::Once;
+
+    const PROGRAM_ID: Pubkey = Pubkey::new_from_array([7; 32]);
+    const NOW: i64 = 1_700_000_000;
+
+    /// Stands in for the runtime: serves the clock and rent sysvars and
+    /// executes the system program's CreateAccount, Transfer, Allocate and
+    /// Assign
+    struct TestRuntime;
+
+    impl SyscallStubs for TestRuntime {
+        fn sol_invoke_signed(
+            &self,
+            instruction: &Instruction,
+            account_infos: &[AccountInfo],
+            signers_seeds: &[&[&[u8]]],
+        ) -> ProgramResult {
+            assert_eq!(instruction.program_id, system_program::ID);
+            let account = |i: usize| {
+                account_infos
+                    .iter()
+                    .find(|info| *info.key == instruction.accounts[i].pubkey)
+                    .unwrap()
+            };
+            let signs = |info: &AccountInfo| {
+                info.is_signer
+                    || signers_seeds.iter().any(|seeds| {
+                        Pubkey::create_program_address(seeds, &PROGRAM_ID).ok() == Some(*info.key)
+                    })
+            };
+            let allocate = |info: &AccountInfo, space: u64| {
+                if !signs(info) {
+                    return Err(ProgramError::MissingRequiredSignature);
+                }
+                if !info.data_is_empty() || *info.owner != system_program::ID {
+                    return Err(ProgramError::AccountAlreadyInitialized);
+                }
+                info.realloc(space as usize, true)
+            };
+            let transfer = |from: &AccountInfo, to: &AccountInfo, lamports: u64| {
+                if !signs(from) {
+                    return Err(ProgramError::MissingRequiredSignature);
+                }
+                **from.try_borrow_mut_lamports()? = from
+                    .lamports()
+                    .checked_sub(lamports)
+                    .ok_or(ProgramError::InsufficientFunds)?;
+                **to.try_borrow_mut_lamports()? += lamports;
+                Ok(())
+            };
+
+            match limited_deserialize(&instruction.data, 1024)
+                .map_err(|_| ProgramError::InvalidInstructionData)?
+            {
+                SystemInstruction::CreateAccount {
+                    lamports,
+                    space,
+                    owner,
+                } => {
+                    // A funded address cannot be created, as on chain
+                    if account(1).lamports() > 0 {
+                        return Err(ProgramError::AccountAlreadyInitialized);
+                    }
+                    transfer(account(0), account(1), lamports)?;
+                    allocate(account(1), space)?;
+                    account(1).assign(&owner);
+                    Ok(())
+                }
+                SystemInstruction::Transfer { lamports } => {
+                    transfer(account(0), account(1), lamports)
+                }
+                SystemInstruction::Allocate { space } => allocate(account(0), space),
+                SystemInstruction::Assign { owner } => {
+                    if !signs(account(0)) {
+                        return Err(ProgramError::MissingRequiredSignature);
+                    }
+                    account(0).assign(&owner);
+                    Ok(())
+                }
+                _ => Err(ProgramError::InvalidInstructionData),
+            }
+        }
+
+        fn sol_get_clock_sysvar(&self, var_addr: *mut u8) -> u64 {
+            let clock = Clock {
+                unix_timestamp: NOW,
+                ..Clock::default()
+            };
+            unsafe { *(var_addr as *mut Clock) = clock };
+            0
+        }
+
+        fn sol_get_rent_sysvar(&self, var_addr: *mut u8) -> u64 {
+            unsafe { *(var_addr as *mut Rent) = Rent::default() };
+            0
+        }
+    }
+
+    fn install_runtime() {
+        static INSTALL: Once = Once::new();
+        INSTALL.call_once(|| {
+            set_syscall_stubs(Box::new(TestRuntime));
+        });
+    }
+
+    /// Backing storage for an `AccountInfo`, laid out like the runtime's
+    /// input so `realloc` works: the original data length sits just before
+    /// the key, and the data follows its own length with room to grow
+    #[repr(C)]
+    struct TestAccount {
+        original_data_len: u32,
+        key: Pubkey,
+        lamports: u64,
+        data: &'static mut [u8],
+        owner: Pubkey,
+    }
+
+    impl TestAccount {
+        fn new(key: Pubkey, lamports: u64, owner: Pubkey) -> Self {
+            Self::with_data(key, lamports, owner, &[])
+        }
+
+        fn with_data(key: Pubkey, lamports: u64, owner: Pubkey, data: &[u8]) -> Self {
+            let buffer: &'static mut [u8] =
+                Box::leak(vec![0; 8 + data.len() + MAX_PERMITTED_DATA_INCREASE].into_boxed_slice());
+            buffer[..8].copy_from_slice(&(data.len() as u64).to_le_bytes());
+            buffer[8..8 + data.len()].copy_from_slice(data);
+            Self {
+                original_data_len: data.len() as u32,
+                key,
+                lamports,
+                data: &mut buffer[8..8 + data.len()],
+                owner,
+            }
+        }
+
+        fn info(&mut self, is_signer: bool, is_writable: bool) -> AccountInfo<'_> {
+            AccountInfo::new(
+                &self.key,
+                is_signer,
+                is_writable,
+                &mut self.lamports,
+                self.data,
+                &self.owner,
+                false,
+                0,
+            )
+        }
+    }
+
+    fn register(document_hash: &str) -> Vec<u8> {
+        DocumentInstruction::RegisterDocument {
+            document_hash: document_hash.to_string(),
+            metadata: Some("{\"title\":\"lease\"}".to_string()),
+        }
+        .try_to_vec()
+        .unwrap()
+    }
+
+    /// A registered document owned by `owner`
+    fn stored_document(owner: Pubkey) -> TestAccount {
+        document_account(&Document {
+            owner,
+            document_hash: "cd".repeat(32),
+            timestamp: NOW as u64,
+            metadata: None,
+            proposed_owner: None,
+        })
+    }
+
+    /// A rent-exempt account holding `document` and the proposed owner
+    /// reserve, as registration sizes it
+    fn document_account(document: &Document) -> TestAccount {
+        let mut data = document.try_to_vec().unwrap();
+        data.resize(data.len() + 32, 0);
+        let lamports = Rent::default().minimum_balance(data.len());
+        TestAccount::with_data(Pubkey::new_unique(), lamports, PROGRAM_ID, &data)
+    }
+
+    fn run(accounts: &[AccountInfo], instruction: DocumentInstruction) -> ProgramResult {
+        process_instruction(&PROGRAM_ID, accounts, &instruction.try_to_vec().unwrap())
+    }
+
+    #[test]
+    fn register_creates_the_document_account() {
+        install_runtime();
+        let owner_key = Pubkey::new_unique();
+        let document_hash = "ab".repeat(32);
+        let mut owner = TestAccount::new(owner_key, 1_000_000_000, system_program::ID);
+        let mut document = TestAccount::new(
+            document_address(&PROGRAM_ID, &owner_key, &document_hash).0,
+            0,
+            system_program::ID,
+        );
+        let mut system = TestAccount::new(system_program::ID, 1, Pubkey::default());
+
+        let owner_info = owner.info(true, true);
+        let document_info = document.info(false, true);
+        let accounts = [
+            owner_info.clone(),
+            document_info.clone(),
+            system.info(false, false),
+        ];
+        process_instruction(&PROGRAM_ID, &accounts, &register(&document_hash)).unwrap();
+
+        assert_eq!(*document_info.owner, PROGRAM_ID);
+        assert!(Rent::default().is_exempt(document_info.lamports(), document_info.data_len()));
+        assert_eq!(
+            owner_info.lamports(),
+            1_000_000_000 - document_info.lamports()
+        );
+
+        let stored = Document::load(&document_info).unwrap();
+        assert_eq!(stored.owner, owner_key);
+        assert_eq!(stored.document_hash, document_hash);
+        assert_eq!(stored.timestamp, NOW as u64);
+        assert_eq!(stored.metadata.as_deref(), Some("{\"title\":\"lease\"}"));
+        assert_eq!(stored.proposed_owner, None);
+
+        // The same owner and hash cannot be registered twice
+        assert_eq!(
+            process_instruction(&PROGRAM_ID, &accounts, &register(&document_hash)),
+            Err(DocumentVerificationError::AccountAlreadyInitialized.into())
+        );
+    }
+
+    #[test]
+    fn accepted_transfer_moves_ownership() {
+        let (alice, bob) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut document = stored_document(alice);
+        let mut current = TestAccount::new(alice, 0, system_program::ID);
+        let mut proposed = TestAccount::new(bob, 0, system_program::ID);
+        let document_info = document.info(false, true);
+
+        run(
+            &[current.info(true, false), document_info.clone()],
+            DocumentInstruction::InitiateTransfer { new_owner: bob },
+        )
+        .unwrap();
+        assert_eq!(Document::load(&document_info).unwrap().owner, alice);
+
+        run(
+            &[proposed.info(true, false), document_info.clone()],
+            DocumentInstruction::AcceptTransfer,
+        )
+        .unwrap();
+        let stored = Document::load(&document_info).unwrap();
+        assert_eq!(stored.owner, bob);
+        assert_eq!(stored.proposed_owner, None);
+    }
+
+    #[test]
+    fn cancelled_transfer_cannot_be_accepted() {
+        let (alice, bob) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut document = stored_document(alice);
+        let mut current = TestAccount::new(alice, 0, system_program::ID);
+        let mut proposed = TestAccount::new(bob, 0, system_program::ID);
+        let document_info = document.info(false, true);
+        let current_info = current.info(true, false);
+
+        run(
+            &[current_info.clone(), document_info.clone()],
+            DocumentInstruction::InitiateTransfer { new_owner: bob },
+        )
+        .unwrap();
+        run(
+            &[current_info.clone(), document_info.clone()],
+            DocumentInstruction::CancelTransfer,
+        )
+        .unwrap();
+        assert_eq!(Document::load(&document_info).unwrap().proposed_owner, None);
+
+        assert_eq!(
+            run(
+                &[proposed.info(true, false), document_info.clone()],
+                DocumentInstruction::AcceptTransfer
+            ),
+            Err(DocumentVerificationError::NotProposedOwner.into())
+        );
+        assert_eq!(
+            run(
+                &[current_info, document_info.clone()],
+                DocumentInstruction::CancelTransfer
+            ),
+            Err(DocumentVerificationError::NoPendingTransfer.into())
+        );
+    }
+
+    #[test]
+    fn only_the_proposed_owner_can_accept() {
+        let (alice, bob, mallory) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        let mut document = stored_document(alice);
+        let mut current = TestAccount::new(alice, 0, system_program::ID);
+        let mut intruder = TestAccount::new(mallory, 0, system_program::ID);
+        let mut proposed = TestAccount::new(bob, 0, system_program::ID);
+        let document_info = document.info(false, true);
+
+        run(
+            &[current.info(true, false), document_info.clone()],
+            DocumentInstruction::InitiateTransfer { new_owner: bob },
+        )
+        .unwrap();
+
+        assert_eq!(
+            run(
+                &[intruder.info(true, false), document_info.clone()],
+                DocumentInstruction::AcceptTransfer
+            ),
+            Err(DocumentVerificationError::NotProposedOwner.into())
+        );
+        assert_eq!(
+            run(
+                &[proposed.info(false, false), document_info.clone()],
+                DocumentInstruction::AcceptTransfer
+            ),
+            Err(ProgramError::MissingRequiredSignature)
+        );
+        assert_eq!(Document::load(&document_info).unwrap().owner, alice);
+    }
+
+    #[test]
+    fn one_step_transfer_requires_the_new_owner_to_sign() {
+        let (alice, bob) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut document = stored_document(alice);
+        let mut current = TestAccount::new(alice, 0, system_program::ID);
+        let mut recipient = TestAccount::new(bob, 0, system_program::ID);
+        let document_info = document.info(false, true);
+        let current_info = current.info(true, false);
+        let recipient_info = recipient.info(false, false);
+        let mut signing_recipient = recipient_info.clone();
+        signing_recipient.is_signer = true;
+
+        assert_eq!(
+            run(
+                &[current_info.clone(), document_info.clone(), recipient_info],
+                DocumentInstruction::TransferOwnership,
+            ),
+            Err(ProgramError::MissingRequiredSignature)
+        );
+        assert_eq!(Document::load(&document_info).unwrap().owner, alice);
+
+        run(
+            &[current_info, document_info.clone(), signing_recipient],
+            DocumentInstruction::TransferOwnership,
+        )
+        .unwrap();
+        assert_eq!(Document::load(&document_info).unwrap().owner, bob);
+    }
+
+    #[test]
+    fn hashes_sharing_a_prefix_get_distinct_addresses() {
+        let owner = Pubkey::new_unique();
+        let prefix = "ab".repeat(16);
+        let first = format!("{prefix}{}", "01".repeat(48));
+        let second = format!("{prefix}{}", "02".repeat(48));
+
+        assert_ne!(
+            document_address(&PROGRAM_ID, &owner, &first).0,
+            document_address(&PROGRAM_ID, &owner, &second).0
+        );
+    }
+
+    #[test]
+    fn errors_map_to_stable_custom_codes() {
+        use DocumentVerificationError::*;
+        let codes = [
+            (NotDocumentOwner, 0),
+            (DocumentAccountMismatch, 1),
+            (MetadataTooLarge, 2),
+            (HashInvalid, 3),
+            (AccountAlreadyInitialized, 4),
+            (DocumentAccountNotWritable, 5),
+            (InvalidDocumentData, 6),
+            (SelfTransfer, 7),
+            (NotProposedOwner, 8),
+            (NoPendingTransfer, 9),
+            (LamportsOverflow, 10),
+            (DocumentNotInitialized, 11),
+        ];
+        for (error, code) in codes {
+            assert_eq!(ProgramError::from(error), ProgramError::Custom(code));
+        }
+    }
+
+    #[test]
+    fn register_rejections_return_their_error_codes() {
+        install_runtime();
+        let owner_key = Pubkey::new_unique();
+        let document_hash = "ab".repeat(32);
+        let mut owner = TestAccount::new(owner_key, 1_000_000_000, system_program::ID);
+        let mut document = TestAccount::new(
+            document_address(&PROGRAM_ID, &owner_key, &document_hash).0,
+            0,
+            system_program::ID,
+        );
+        let mut elsewhere = TestAccount::new(Pubkey::new_unique(), 0, system_program::ID);
+        let mut system = TestAccount::new(system_program::ID, 1, Pubkey::default());
+        let owner_info = owner.info(true, true);
+        let system_info = system.info(false, false);
+        let accounts = [
+            owner_info.clone(),
+            document.info(false, true),
+            system_info.clone(),
+        ];
+
+        assert_eq!(
+            process_instruction(&PROGRAM_ID, &accounts, &register(&"AB".repeat(32))),
+            Err(ProgramError::Custom(3))
+        );
+        let oversized = DocumentInstruction::RegisterDocument {
+            document_hash: document_hash.clone(),
+            metadata: Some("x".repeat(MAX_METADATA_LEN + 1)),
+        };
+        assert_eq!(run(&accounts, oversized), Err(ProgramError::Custom(2)));
+        assert_eq!(
+            process_instruction(
+                &PROGRAM_ID,
+                &[
+                    owner_info.clone(),
+                    elsewhere.info(false, true),
+                    system_info.clone()
+                ],
+                &register(&document_hash)
+            ),
+            Err(ProgramError::Custom(1))
+        );
+        let mut read_only = accounts.clone();
+        read_only[1].is_writable = false;
+        assert_eq!(
+            process_instruction(&PROGRAM_ID, &read_only, &register(&document_hash)),
+            Err(ProgramError::Custom(5))
+        );
+    }
+
+    #[test]
+    fn owner_checks_return_their_error_codes() {
+        let (alice, mallory) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut document = stored_document(alice);
+        let mut current = TestAccount::new(alice, 0, system_program::ID);
+        let mut intruder = TestAccount::new(mallory, 0, system_program::ID);
+        let mut system = TestAccount::new(system_program::ID, 1, Pubkey::default());
+        let document_info = document.info(false, true);
+
+        assert_eq!(
+            run(
+                &[
+                    intruder.info(true, true),
+                    document_info.clone(),
+                    system.info(false, false)
+                ],
+                DocumentInstruction::UpdateMetadata {
+                    metadata: "{}".to_string()
+                },
+            ),
+            Err(ProgramError::Custom(0))
+        );
+        assert_eq!(
+            run(
+                &[current.info(true, false), document_info.clone()],
+                DocumentInstruction::InitiateTransfer { new_owner: alice },
+            ),
+            Err(ProgramError::Custom(7))
+        );
+
+        let mut empty =
+            TestAccount::with_data(Pubkey::new_unique(), 1_000_000, PROGRAM_ID, &[0; 128]);
+        assert_eq!(
+            run(
+                &[current.info(true, true), empty.info(false, true)],
+                DocumentInstruction::CloseDocument,
+            ),
+            Err(ProgramError::Custom(11))
+        );
+    }
+
+    #[test]
+    fn one_step_transfer_cancels_a_pending_proposal() {
+        let (alice, bob, carol) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        let mut document = stored_document(alice);
+        let mut current = TestAccount::new(alice, 0, system_program::ID);
+        let mut proposed = TestAccount::new(bob, 0, system_program::ID);
+        let mut recipient = TestAccount::new(carol, 0, system_program::ID);
+        let document_info = document.info(false, true);
+        let current_info = current.info(true, false);
+
+        run(
+            &[current_info.clone(), document_info.clone()],
+            DocumentInstruction::InitiateTransfer { new_owner: bob },
+        )
+        .unwrap();
+        run(
+            &[
+                current_info,
+                document_info.clone(),
+                recipient.info(true, false),
+            ],
+            DocumentInstruction::TransferOwnership,
+        )
+        .unwrap();
+
+        let stored = Document::load(&document_info).unwrap();
+        assert_eq!(stored.owner, carol);
+        assert_eq!(stored.proposed_owner, None);
+        assert_eq!(
+            run(
+                &[proposed.info(true, false), document_info.clone()],
+                DocumentInstruction::AcceptTransfer
+            ),
+            Err(DocumentVerificationError::NotProposedOwner.into())
+        );
+    }
+
+    fn update_metadata(metadata: String) -> DocumentInstruction {
+        DocumentInstruction::UpdateMetadata { metadata }
+    }
+
+    #[test]
+    fn growing_metadata_tops_up_rent_from_the_owner() {
+        install_runtime();
+        let alice = Pubkey::new_unique();
+        let mut owner = TestAccount::new(alice, 1_000_000_000, system_program::ID);
+        let mut document = stored_document(alice);
+        let mut system = TestAccount::new(system_program::ID, 1, Pubkey::default());
+        let document_info = document.info(false, true);
+        let owner_info = owner.info(true, true);
+        let accounts = [
+            owner_info.clone(),
+            document_info.clone(),
+            system.info(false, false),
+        ];
+        let before = document_info.lamports();
+
+        let metadata = "x".repeat(400);
+        run(&accounts, update_metadata(metadata.clone())).unwrap();
+
+        let stored = Document::load(&document_info).unwrap();
+        let space = stored.try_to_vec().unwrap().len() + 32;
+        assert_eq!(stored.metadata, Some(metadata));
+        assert_eq!(document_info.data_len(), space);
+        assert_eq!(
+            document_info.lamports(),
+            Rent::default().minimum_balance(space)
+        );
+        assert_eq!(
+            owner_info.lamports(),
+            1_000_000_000 - (document_info.lamports() - before)
+        );
+    }
+
+    #[test]
+    fn shrinking_metadata_refunds_rent_to_the_owner() {
+        install_runtime();
+        let alice = Pubkey::new_unique();
+        let mut owner = TestAccount::new(alice, 0, system_program::ID);
+        let mut document = document_account(&Document {
+            owner: alice,
+            document_hash: "cd".repeat(32),
+            timestamp: NOW as u64,
+            metadata: Some("x".repeat(400)),
+            proposed_owner: None,
+        });
+        let mut system = TestAccount::new(system_program::ID, 1, Pubkey::default());
+        let document_info = document.info(false, true);
+        let owner_info = owner.info(true, true);
+        let accounts = [
+            owner_info.clone(),
+            document_info.clone(),
+            system.info(false, false),
+        ];
+        let before = document_info.lamports();
+
+        run(&accounts, update_metadata("{}".to_string())).unwrap();
+
+        let space = Document::load(&document_info)
+            .unwrap()
+            .try_to_vec()
+            .unwrap()
+            .len()
+            + 32;
+        assert_eq!(document_info.data_len(), space);
+        assert_eq!(
+            document_info.lamports(),
+            Rent::default().minimum_balance(space)
+        );
+        assert_eq!(owner_info.lamports(), before - document_info.lamports());
+    }
+
+    #[test]
+    fn metadata_is_accepted_up_to_its_maximum_length() {
+        install_runtime();
+        let alice = Pubkey::new_unique();
+        let mut owner = TestAccount::new(alice, 1_000_000_000, system_program::ID);
+        let mut document = stored_document(alice);
+        let mut system = TestAccount::new(system_program::ID, 1, Pubkey::default());
+        let document_info = document.info(false, true);
+        let accounts = [
+            owner.info(true, true),
+            document_info.clone(),
+            system.info(false, false),
+        ];
+
+        run(&accounts, update_metadata("x".repeat(MAX_METADATA_LEN))).unwrap();
+        assert_eq!(
+            run(&accounts, update_metadata("y".repeat(MAX_METADATA_LEN + 1))),
+            Err(DocumentVerificationError::MetadataTooLarge.into())
+        );
+        assert_eq!(
+            Document::load(&document_info).unwrap().metadata,
+            Some("x".repeat(MAX_METADATA_LEN))
+        );
+    }
+
+    #[test]
+    fn register_takes_over_a_prefunded_address() {
+        install_runtime();
+        let owner_key = Pubkey::new_unique();
+        let document_hash = "ab".repeat(32);
+        let mut owner = TestAccount::new(owner_key, 1_000_000_000, system_program::ID);
+        // Someone sent a few lamports to the address before registration
+        let mut document = TestAccount::new(
+            document_address(&PROGRAM_ID, &owner_key, &document_hash).0,
+            5_000,
+            system_program::ID,
+        );
+        let mut system = TestAccount::new(system_program::ID, 1, Pubkey::default());
+        let owner_info = owner.info(true, true);
+        let document_info = document.info(false, true);
+        let accounts = [
+            owner_info.clone(),
+            document_info.clone(),
+            system.info(false, false),
+        ];
+
+        process_instruction(&PROGRAM_ID, &accounts, &register(&document_hash)).unwrap();
+
+        let rent = Rent::default().minimum_balance(document_info.data_len());
+        assert_eq!(*document_info.owner, PROGRAM_ID);
+        assert_eq!(document_info.lamports(), rent);
+        assert_eq!(owner_info.lamports(), 1_000_000_000 - (rent - 5_000));
+        assert_eq!(Document::load(&document_info).unwrap().owner, owner_key);
+    }
+
+    #[test]
+    fn only_the_owner_can_close_a_document() {
+        let (alice, mallory) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut document = stored_document(alice);
+        let mut owner = TestAccount::new(alice, 0, system_program::ID);
+        let mut intruder = TestAccount::new(mallory, 0, system_program::ID);
+        let document_info = document.info(false, true);
+        let intruder_info = intruder.info(true, true);
+        let data = document_info.data.borrow().to_vec();
+        let lamports = document_info.lamports();
+
+        assert_eq!(
+            run(
+                &[intruder_info.clone(), document_info.clone()],
+                DocumentInstruction::CloseDocument
+            ),
+            Err(ProgramError::Custom(0))
+        );
+        assert_eq!(*document_info.data.borrow(), &data[..]);
+        assert_eq!(document_info.lamports(), lamports);
+        assert_eq!(*document_info.owner, PROGRAM_ID);
+        assert_eq!(intruder_info.lamports(), 0);
+
+        let owner_info = owner.info(true, true);
+        run(
+            &[owner_info.clone(), document_info.clone()],
+            DocumentInstruction::CloseDocument,
+        )
+        .unwrap();
+        assert_eq!(owner_info.lamports(), lamports);
+        assert_eq!(document_info.lamports(), 0);
+    }
+
+    #[test]
+    fn one_step_transfer_rejects_a_default_or_missing_new_owner() {
+        let alice = Pubkey::new_unique();
+        let mut document = stored_document(alice);
+        let mut current = TestAccount::new(alice, 0, system_program::ID);
+        let mut nobody = TestAccount::new(Pubkey::default(), 0, system_program::ID);
+        let document_info = document.info(false, true);
+        let current_info = current.info(true, false);
+
+        assert_eq!(
+            run(
+                &[
+                    current_info.clone(),
+                    document_info.clone(),
+                    nobody.info(true, false)
+                ],
+                DocumentInstruction::TransferOwnership,
+            ),
+            Err(ProgramError::InvalidArgument)
+        );
+        assert_eq!(
+            run(
+                &[current_info, document_info.clone()],
+                DocumentInstruction::TransferOwnership
+            ),
+            Err(ProgramError::NotEnoughAccountKeys)
+        );
+        assert_eq!(Document::load(&document_info).unwrap().owner, alice);
+    }
+}
@@ -0,0 +1,74 @@
+//! Types and checks shared by the Blokdoc Anchor and native programs.
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::pubkey::Pubkey;
+
+/// Maximum length in bytes of a document hash (a hex-encoded SHA-512 digest).
+pub const MAX_HASH_LEN: usize = 128;
+
+/// Return data of the native `VerifyDocument` and Anchor `verify_document`
+/// instructions
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct VerificationResult {
+    /// Whether the stored hash equals the expected hash
+    pub matched: bool,
+    /// Current document owner
+    pub owner: Pubkey,
+    /// Registration time
+    pub timestamp: i64,
+}
+
+/// Whether `hash` consists only of lowercase hex characters.
+pub fn is_lowercase_hex(hash: &str) -> bool {
+    hash.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'))
+}
+
+/// Decodes a lowercase hex digest of exactly 32 bytes, as stored in
+/// `Document::document_hash_bytes`.
+pub fn decode_digest_32(hash: &str) -> Option<[u8; 32]> {
+    let nibble = |b: u8| match b {
+        b'0'..=b'9' => Some(b - b'0'),
+        b'a'..=b'f' => Some(b - b'a' + 10),
+        _ => None,
+    };
+
+    let hex = hash.as_bytes();
+    if hex.len() != 64 {
+        return None;
+    }
+    let mut digest = [0u8; 32];
+    for (byte, pair) in digest.iter_mut().zip(hex.chunks(2)) {
+        *byte = nibble(pair[0])? << 4 | nibble(pair[1])?;
+    }
+    Some(digest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn to_hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|b| format!("{b:02x}")).collect()
+    }
+
+    #[test]
+    fn digest_round_trips_through_hex() {
+        let digest: [u8; 32] = core::array::from_fn(|i| (i as u8).wrapping_mul(37));
+        let hex = to_hex(&digest);
+
+        assert!(is_lowercase_hex(&hex));
+        assert_eq!(decode_digest_32(&hex), Some(digest));
+        assert_eq!(decode_digest_32(&"ff".repeat(32)), Some([0xff; 32]));
+    }
+
+    #[test]
+    fn digest_rejects_other_lengths_and_characters() {
+        assert_eq!(decode_digest_32(""), None);
+        assert_eq!(decode_digest_32(&"a".repeat(63)), None);
+        assert_eq!(decode_digest_32(&"a".repeat(66)), None);
+        // A SHA-512 digest is valid hex but not 32 bytes
+        assert_eq!(decode_digest_32(&"ab".repeat(64)), None);
+        assert_eq!(decode_digest_32(&"AB".repeat(32)), None);
+        assert_eq!(decode_digest_32(&format!("{}zz", "ab".repeat(31))), None);
+    }
+}
@@ -1,7 +1,7 @@
 import { Connection, PublicKey, TransactionInstruction, SystemProgram, Transaction } from '@solana/web3.js';
 import { BN } from '@project-serum/anchor';
 import * as crypto from 'crypto';
-import { getProgram, findDocumentManagerPDA, findDocumentPDA, findSignaturePDA } from './program';
+import { getProgram, findDocumentManagerPDA, findAuthorityStatsPDA, findDocumentPDA, findSignaturePDA } from './program';
 
 // Document creation and management
 export interface DocumentMetadata {
@@ -51,11 +51,13 @@ export const registerDocument = async (
     const program = await getProgram(connection, wallet);
     const [documentManagerPDA] = await findDocumentManagerPDA();
     
-    // Fetch document count for creating the new PDA
-    const documentManager = await program.account.documentManager.fetch(documentManagerPDA);
-    const documentCount = documentManager.documentCount.toNumber();
+    // The new document's index is this wallet's own document count; a
+    // wallet registering for the first time has no stats account yet
+    const [authorityStatsPDA] = await findAuthorityStatsPDA(wallet.publicKey);
+    const authorityStats = await program.account.authorityStats.fetchNullable(authorityStatsPDA);
+    const documentIndex = authorityStats ? authorityStats.documentCount.toNumber() : 0;
     
-    const [documentPDA] = await findDocumentPDA(wallet.publicKey, documentCount);
+    const [documentPDA] = await findDocumentPDA(wallet.publicKey, documentIndex);
     
     // Current timestamp
     const timestamp = Math.floor(Date.now() / 1000);
@@ -65,6 +67,7 @@ export const registerDocument = async (
       .registerDocument(documentHash, documentName, documentType, new BN(timestamp))
       .accounts({
         documentManager: documentManagerPDA,
+        authorityStats: authorityStatsPDA,
         document: documentPDA,
         authority: wallet.publicKey,
         systemProgram: SystemProgram.programId,
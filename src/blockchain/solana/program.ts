@@ -49,15 +49,28 @@ export const findDocumentManagerPDA = async (): Promise<[PublicKey, number]> =>
   );
 };
 
+export const findAuthorityStatsPDA = async (
+  authority: PublicKey
+): Promise<[PublicKey, number]> => {
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from('authority'), authority.toBuffer()],
+    getProgramId()
+  );
+};
+
+// Documents are indexed per registrant: the seed is the wallet's own
+// document count as a little-endian u64
 export const findDocumentPDA = async (
   authority: PublicKey,
-  documentCount: number
+  documentIndex: number
 ): Promise<[PublicKey, number]> => {
+  const index = Buffer.alloc(8);
+  index.writeBigUInt64LE(BigInt(documentIndex));
   return PublicKey.findProgramAddressSync(
     [
       Buffer.from('document'),
       authority.toBuffer(),
-      Buffer.from(new Uint8Array(documentCount.toString().split('').map(char => char.charCodeAt(0))))
+      index
     ],
     getProgramId()
   );